@@ -11,38 +11,23 @@ pub trait FromOpenApi: Sized {
     ) -> anyhow::Result<Self>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct FromOpenApiOptions {
     pub include_arrays: bool,
     pub include_schemas: Vec<String>,
 }
 
-impl Default for FromOpenApiOptions {
-    fn default() -> Self {
-        Self {
-            include_arrays: false,
-            include_schemas: vec![],
-        }
-    }
-}
-
 impl FromOpenApi for Schema {
     fn try_from_openapi(spec: oa::OpenAPI, options: &FromOpenApiOptions) -> anyhow::Result<Self> {
         let mut tables = Vec::new();
         for (schema_name, schema) in spec.schemas.iter().filter(|(schema_name, _)| {
-            if options.include_schemas.contains(schema_name) {
-                true
-            } else if schema_name.ends_with("Response") {
-                false
-            } else {
-                true
-            }
+            options.include_schemas.contains(schema_name) || !schema_name.ends_with("Response")
         }) {
             let schema = schema.resolve(&spec);
-            let Some(mut columns) = schema_to_columns(&schema, &spec, options)? else {
+            let Some(mut columns) = schema_to_columns(schema, &spec, options)? else {
                 continue;
             };
-            let pkey_candidates = pkey_column_names(&schema_name);
+            let pkey_candidates = pkey_column_names(schema_name);
             for col in &mut columns {
                 if pkey_candidates.contains(&col.name) {
                     col.primary_key = true;
@@ -54,10 +39,18 @@ impl FromOpenApi for Schema {
                 name: schema_name.to_case(Case::Snake),
                 columns,
                 indexes: vec![],
+                primary_key: None,
+                constraints: vec![],
+                comment: schema.data.description.clone(),
             };
             tables.push(table);
         }
-        Ok(Schema { tables })
+        Ok(Schema {
+            tables,
+            enums: vec![],
+            views: vec![],
+            sequences: vec![],
+        })
     }
 }
 
@@ -123,7 +116,7 @@ fn schema_to_columns(
         if primary_key {
             nullable = false;
         }
-        if prop.is_required(&name) {
+        if prop.is_required(name) {
             nullable = false;
         }
         if prop
@@ -151,6 +144,8 @@ fn schema_to_columns(
             nullable,
             default: None,
             constraint: None,
+            generated: None,
+            comment: prop.data.description.clone(),
         };
         columns.push(column);
     }
@@ -166,6 +161,7 @@ mod test {
     use openapiv3 as oa;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_format_date() {
         let mut z = oa::Schema::new_object();
 
@@ -177,6 +173,7 @@ mod test {
         z.properties_mut().insert("date", int_format_date);
 
         let mut int_null_as_zero = oa::Schema::new_integer();
+        int_null_as_zero.data.description = Some("Zero when unknown".to_string());
         int_null_as_zero
             .data
             .extensions
@@ -196,6 +193,7 @@ mod test {
         let int_null_as_zero = &columns[1];
         assert_eq!(int_null_as_zero.name, "int_null_as_zero");
         assert_eq!(int_null_as_zero.nullable, true);
+        assert_eq!(int_null_as_zero.comment.as_deref(), Some("Zero when unknown"));
     }
 
     #[test]
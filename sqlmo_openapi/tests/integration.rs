@@ -14,12 +14,18 @@ pub fn test_run_sql_migration() -> Result<()> {
     let spec: OpenAPI = serde_yaml::from_reader(yaml)?;
     let current = Schema::default();
     let mut desired = Schema::try_from_openapi(spec, &FromOpenApiOptions::default())?;
-    desired.name_schema("public");
+    desired.default_schema("public");
     let migration = current.migrate_to(
         desired,
         &MigrationOptions {
             debug: false,
             allow_destructive: false,
+            default_schema: None,
+            online: false,
+            backfill: Default::default(),
+            using: Default::default(),
+            backfill_batch_size: None,
+            dialect: None,
         },
     )?;
 
@@ -0,0 +1,237 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use sqlx::{Connection, PgConnection, Row};
+
+use sqlmo::migrate::{AppliedMigration, MigrationHistory, Statement};
+use sqlmo::query::Backfill;
+use sqlmo::{Dialect, Migration, ToSql};
+
+/// Options for `apply_migration`.
+#[derive(Debug, Clone)]
+pub struct ApplyOptions {
+    /// Recorded in the history table. Defaults to `Migration::name`.
+    pub name: Option<String>,
+    pub history: MigrationHistory,
+    /// Key of the `pg_advisory_lock` held while the migration runs, so concurrent deploys run
+    /// one at a time.
+    pub lock_key: i64,
+    /// `lock_timeout` for the migration's statements.
+    pub lock_timeout: Option<Duration>,
+    /// `statement_timeout` for the migration's statements.
+    pub statement_timeout: Option<Duration>,
+}
+
+impl Default for ApplyOptions {
+    fn default() -> Self {
+        ApplyOptions {
+            name: None,
+            history: MigrationHistory::default(),
+            // "sqlmo"
+            lock_key: 0x73716c6d6f,
+            lock_timeout: None,
+            statement_timeout: None,
+        }
+    }
+}
+
+impl ApplyOptions {
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
+    pub fn statement_timeout(mut self, timeout: Duration) -> Self {
+        self.statement_timeout = Some(timeout);
+        self
+    }
+}
+
+/// The migrations recorded in the history table, oldest first.
+pub async fn applied_migrations(
+    conn: &mut PgConnection,
+    history: &MigrationHistory,
+) -> Result<Vec<AppliedMigration>> {
+    let sql = history.list(Dialect::Postgres).to_sql(Dialect::Postgres);
+    let rows = sqlx::query(&sql).fetch_all(conn).await?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(AppliedMigration {
+                name: row.try_get("name")?,
+                checksum: row.try_get("checksum")?,
+                applied_at: row.try_get("applied_at")?,
+                execution_time_ms: row.try_get("execution_time_ms")?,
+            })
+        })
+        .collect()
+}
+
+/// Apply `migration` and record it in the history table, unless a migration with the same name
+/// was already applied, in which case that record is returned.
+///
+/// The statements run in the transactions of `Migration::transactions`, the last of which also
+/// records the migration. Statements that can't run in a transaction, like `CREATE INDEX
+/// CONCURRENTLY`, run on their own, so a failure after one of them leaves the migration
+/// partially applied.
+pub async fn apply_migration(
+    conn: &mut PgConnection,
+    migration: &Migration,
+    options: &ApplyOptions,
+) -> Result<AppliedMigration> {
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(options.lock_key)
+        .execute(&mut *conn)
+        .await?;
+    let result = apply_locked(conn, migration, options).await;
+    let mut reset = String::new();
+    if options.lock_timeout.is_some() {
+        reset.push_str("RESET lock_timeout;");
+    }
+    if options.statement_timeout.is_some() {
+        reset.push_str("RESET statement_timeout;");
+    }
+    let reset = match reset.is_empty() {
+        true => Ok(()),
+        false => sqlx::raw_sql(&reset).execute(&mut *conn).await.map(|_| ()),
+    };
+    let unlock = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(options.lock_key)
+        .execute(&mut *conn)
+        .await;
+    let applied = result?;
+    reset?;
+    unlock?;
+    Ok(applied)
+}
+
+async fn apply_locked(
+    conn: &mut PgConnection,
+    migration: &Migration,
+    options: &ApplyOptions,
+) -> Result<AppliedMigration> {
+    let history = &options.history;
+    let create = history.create_table().to_sql(Dialect::Postgres);
+    sqlx::raw_sql(&create).execute(&mut *conn).await?;
+
+    let name = options.name.clone().unwrap_or_else(|| migration.name());
+    let checksum = migration.checksum(Dialect::Postgres);
+    let applied = applied_migrations(conn, history).await?;
+    if let Some(applied) = applied.into_iter().find(|m| m.name == name) {
+        if applied.checksum != checksum {
+            bail!(
+                "Migration {} was applied with checksum {}, but now has checksum {}",
+                name,
+                applied.checksum,
+                checksum
+            );
+        }
+        return Ok(applied);
+    }
+
+    let mut timeouts = String::new();
+    if let Some(timeout) = options.lock_timeout {
+        timeouts.push_str(&format!("SET lock_timeout = '{}ms'; ", timeout.as_millis()));
+    }
+    if let Some(timeout) = options.statement_timeout {
+        timeouts.push_str(&format!("SET statement_timeout = '{}ms'; ", timeout.as_millis()));
+    }
+    if !timeouts.is_empty() {
+        sqlx::raw_sql(&timeouts).execute(&mut *conn).await?;
+    }
+
+    let batches = migration.transactions();
+    let total = migration.statements.len();
+    let start = Instant::now();
+    let record = history.record(Dialect::Postgres).to_sql(Dialect::Postgres);
+    let mut recorded = false;
+    let batch_count = batches.len();
+    for (n, (transactional, batch)) in batches.into_iter().enumerate() {
+        if !transactional {
+            for (i, statement) in batch {
+                let sql = statement.to_sql(Dialect::Postgres);
+                let result = match statement {
+                    Statement::Backfill(b) if b.is_batched() => backfill(conn, b).await,
+                    _ => sqlx::raw_sql(&sql)
+                        .execute(&mut *conn)
+                        .await
+                        .map(|_| ())
+                        .map_err(Into::into),
+                };
+                result
+                    .with_context(|| format!("Statement {} of {} failed: {}", i + 1, total, sql))?;
+            }
+            continue;
+        }
+        let mut tx = conn.begin().await?;
+        for (i, statement) in batch {
+            let sql = statement.to_sql(Dialect::Postgres);
+            sqlx::raw_sql(&sql)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Statement {} of {} failed: {}", i + 1, total, sql))?;
+        }
+        // the migration is recorded along with its last statements
+        if n + 1 == batch_count {
+            sqlx::query(&record)
+                .bind(&name)
+                .bind(&checksum)
+                .bind(start.elapsed().as_millis() as i64)
+                .execute(&mut *tx)
+                .await?;
+            recorded = true;
+        }
+        tx.commit().await?;
+    }
+    if !recorded {
+        sqlx::query(&record)
+            .bind(&name)
+            .bind(&checksum)
+            .bind(start.elapsed().as_millis() as i64)
+            .execute(&mut *conn)
+            .await?;
+    }
+    let applied = applied_migrations(conn, history).await?;
+    applied
+        .into_iter()
+        .find(|m| m.name == name)
+        .context("Migration wasn't recorded")
+}
+
+/// Run a batched backfill one range of keys at a time, committing each.
+async fn backfill(conn: &mut PgConnection, backfill: &Backfill) -> Result<()> {
+    let (Some(bounds), Some(batch)) = (
+        backfill.bounds(Dialect::Postgres),
+        backfill.batch(Dialect::Postgres),
+    ) else {
+        bail!("Backfill of {}.{} isn't batched", backfill.table, backfill.column);
+    };
+    let row = sqlx::query(&bounds.to_sql(Dialect::Postgres))
+        .fetch_one(&mut *conn)
+        .await?;
+    let min: Option<i64> = row.try_get(0)?;
+    let max: Option<i64> = row.try_get(1)?;
+    let (Some(min), Some(max)) = (min, max) else {
+        // the table is empty
+        return Ok(());
+    };
+    let batch = batch.to_sql(Dialect::Postgres);
+    let size = i64::try_from(backfill.batch_size).unwrap_or(i64::MAX);
+    let mut start = min;
+    loop {
+        let end = start.saturating_add(size);
+        sqlx::query(&batch)
+            .bind(start)
+            .bind(end)
+            .execute(&mut *conn)
+            .await?;
+        if end > max {
+            return Ok(());
+        }
+        start = end;
+    }
+}
@@ -1,9 +1,14 @@
 use std::str::FromStr;
-use anyhow::{Error, Result};
+use anyhow::{anyhow, Error, Result};
 use itertools::Itertools;
-use sqlx::PgConnection;
+use sqlx::{Connection, PgConnection};
 
-use sqlmo::{Schema, Column, Table, schema};
+use sqlmo::{Schema, Column, Dialect, Expr, Table, ToSql, schema};
+use sqlmo::migrate::{DriftOptions, DriftReport};
+
+mod apply;
+
+pub use apply::{apply_migration, applied_migrations, ApplyOptions};
 
 
 #[allow(async_fn_in_trait)]
@@ -21,6 +26,15 @@ struct SchemaColumn {
     pub data_type: String,
     pub numeric_precision: Option<i32>,
     pub numeric_scale: Option<i32>,
+    pub character_maximum_length: Option<i32>,
+    pub datetime_precision: Option<i32>,
+    pub interval_type: Option<String>,
+    pub is_identity: String,
+    pub identity_generation: Option<String>,
+    pub is_generated: String,
+    pub generation_expression: Option<String>,
+    pub column_default: Option<String>,
+    pub udt_name: String,
     pub inner_type: Option<String>,
 }
 
@@ -33,6 +47,116 @@ async fn query_schema_columns(conn: &mut PgConnection, schema_name: &str) -> Res
     Ok(result)
 }
 
+#[derive(sqlx::FromRow)]
+struct EnumType {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+async fn query_enums(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<schema::Enum>> {
+    let s = include_str!("sql/query_enums.sql");
+    let result = sqlx::query_as::<_, EnumType>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?;
+    Ok(result
+        .into_iter()
+        .map(|e| schema::Enum {
+            schema: Some(schema_name.to_string()),
+            name: e.name,
+            variants: e.variants,
+        })
+        .collect())
+}
+
+/// User-defined types are read as `Type::Other(udt_name)`. Resolve the ones that are enums.
+fn resolve_enum_type(typ: &mut schema::Type, enums: &[schema::Enum]) {
+    match typ {
+        schema::Type::Other(name) if enums.iter().any(|e| &e.name == name) => {
+            *typ = schema::Type::Enum(name.clone());
+        }
+        schema::Type::Array(inner) => resolve_enum_type(inner, enums),
+        _ => {}
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ViewDefinition {
+    pub name: String,
+    pub definition: String,
+    pub materialized: bool,
+}
+
+async fn query_views(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<schema::View>> {
+    let s = include_str!("sql/query_views.sql");
+    let result = sqlx::query_as::<_, ViewDefinition>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?;
+    Ok(result
+        .into_iter()
+        .map(|v| schema::View {
+            schema: Some(schema_name.to_string()),
+            name: v.name,
+            query: schema::ViewQuery::Raw(v.definition.trim().trim_end_matches(';').to_string()),
+            materialized: v.materialized,
+        })
+        .collect())
+}
+
+#[derive(sqlx::FromRow)]
+struct SequenceDefinition {
+    pub name: String,
+    pub data_type: String,
+    pub start_value: i64,
+    pub increment: i64,
+    pub minimum_value: i64,
+    pub maximum_value: i64,
+    pub cycle: bool,
+    pub owned_by_table: Option<String>,
+    pub owned_by_column: Option<String>,
+}
+
+impl TryInto<schema::Sequence> for SequenceDefinition {
+    type Error = Error;
+
+    fn try_into(self) -> std::result::Result<schema::Sequence, Self::Error> {
+        let owned_by = match (self.owned_by_table, self.owned_by_column) {
+            (Some(table), Some(column)) => Some(schema::OwnedBy { table, column }),
+            _ => None,
+        };
+        Ok(schema::Sequence {
+            schema: None,
+            name: self.name,
+            options: schema::SequenceOptions {
+                typ: Some(schema::Type::from_str(&self.data_type)?),
+                start: Some(self.start_value),
+                increment: Some(self.increment),
+                min_value: Some(self.minimum_value),
+                max_value: Some(self.maximum_value),
+                cycle: Some(self.cycle),
+                owned_by,
+            },
+        })
+    }
+}
+
+async fn query_sequences(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<schema::Sequence>> {
+    let s = include_str!("sql/query_sequences.sql");
+    let result = sqlx::query_as::<_, SequenceDefinition>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?;
+    result
+        .into_iter()
+        .map(|s| {
+            let mut sequence: schema::Sequence = s.try_into()?;
+            sequence.schema = Some(schema_name.to_string());
+            Ok(sequence)
+        })
+        .collect()
+}
+
 #[derive(sqlx::FromRow)]
 struct TableSchema {
     #[allow(dead_code)]
@@ -50,15 +174,54 @@ async fn query_table_names(conn: &mut PgConnection, schema_name: &str) -> Result
 }
 
 #[derive(sqlx::FromRow)]
-#[allow(dead_code)]
 struct ForeignKey {
-    pub table_schema: String,
     pub constraint_name: String,
     pub table_name: String,
-    pub column_name: String,
+    pub columns: Vec<String>,
     pub foreign_table_schema: String,
     pub foreign_table_name: String,
-    pub foreign_column_name: String,
+    pub foreign_columns: Vec<String>,
+    pub delete_action: String,
+    pub update_action: String,
+    pub match_type: String,
+    pub deferrable: bool,
+    pub deferred: bool,
+}
+
+impl ForeignKey {
+    /// Convert to the sqlmo model. The referenced schema is only kept if it differs from the
+    /// schema being introspected.
+    fn to_foreign_key(&self, schema_name: &str) -> schema::ForeignKey {
+        schema::ForeignKey {
+            schema: Some(self.foreign_table_schema.clone()).filter(|s| s != schema_name),
+            table: self.foreign_table_name.clone(),
+            columns: self.foreign_columns.clone(),
+            on_delete: referential_action(&self.delete_action),
+            on_update: referential_action(&self.update_action),
+            match_type: match self.match_type.as_str() {
+                "f" => Some(schema::MatchType::Full),
+                "p" => Some(schema::MatchType::Partial),
+                _ => None,
+            },
+            deferrable: match (self.deferrable, self.deferred) {
+                (false, _) => None,
+                (true, false) => Some(schema::Deferrable::InitiallyImmediate),
+                (true, true) => Some(schema::Deferrable::InitiallyDeferred),
+            },
+        }
+    }
+}
+
+/// Decode `pg_constraint.confdeltype`/`confupdtype`. `NO ACTION` is the default and maps to `None`.
+fn referential_action(code: &str) -> Option<schema::ReferentialAction> {
+    use schema::ReferentialAction::*;
+    match code {
+        "r" => Some(Restrict),
+        "c" => Some(Cascade),
+        "n" => Some(SetNull),
+        "d" => Some(SetDefault),
+        _ => None,
+    }
 }
 
 async fn query_constraints(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<ForeignKey>> {
@@ -69,6 +232,151 @@ async fn query_constraints(conn: &mut PgConnection, schema_name: &str) -> Result
         .await?)
 }
 
+#[derive(sqlx::FromRow)]
+struct TableConstraint {
+    pub constraint_name: String,
+    pub table_name: String,
+    pub constraint_type: String,
+    pub columns: Vec<String>,
+    pub definition: String,
+}
+
+async fn query_table_constraints(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<TableConstraint>> {
+    let s = include_str!("sql/query_table_constraints.sql");
+    Ok(sqlx::query_as::<_, TableConstraint>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?)
+}
+
+impl TryInto<schema::TableConstraint> for TableConstraint {
+    type Error = Error;
+
+    fn try_into(self) -> std::result::Result<schema::TableConstraint, Self::Error> {
+        let definition = self.definition.trim_end_matches(" NOT VALID");
+        let (columns, constraint) = match self.constraint_type.as_str() {
+            "u" => (self.columns, schema::Constraint::Unique),
+            "c" => {
+                let expr = definition.strip_prefix("CHECK ").ok_or_else(|| anyhow!("Unexpected check constraint: {}", definition))?;
+                (vec![], schema::Constraint::Check(Expr::Raw(strip_parens(expr).to_string())))
+            }
+            "x" => (vec![], schema::Constraint::Exclude(parse_exclude(definition)?)),
+            z => return Err(anyhow!("Unexpected constraint type: {}", z)),
+        };
+        Ok(schema::TableConstraint {
+            name: self.constraint_name,
+            columns,
+            constraint,
+        })
+    }
+}
+
+fn strip_parens(s: &str) -> &str {
+    s.trim()
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .unwrap_or(s)
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim().trim_matches('"')
+}
+
+/// Parse the output of `pg_get_constraintdef` for an exclusion constraint, e.g.
+/// `EXCLUDE USING gist (room WITH =, during WITH &&) WHERE ((NOT cancelled))`.
+fn parse_exclude(definition: &str) -> Result<schema::Exclude> {
+    let err = || anyhow!("Unexpected exclusion constraint: {}", definition);
+    let rest = definition.strip_prefix("EXCLUDE USING ").ok_or_else(err)?;
+    let (using, rest) = rest.split_once(' ').ok_or_else(err)?;
+    let mut exclude = schema::Exclude::new(using);
+
+    let mut depth = 0;
+    let mut start = 0;
+    let mut end = None;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => {
+                depth += 1;
+                if depth == 1 {
+                    start = i + 1;
+                }
+            }
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+            }
+            ',' if depth == 1 => {
+                exclude.elements.push(parse_exclude_element(&rest[start..i]).ok_or_else(err)?);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let end = end.ok_or_else(err)?;
+    exclude.elements.push(parse_exclude_element(&rest[start..end]).ok_or_else(err)?);
+    if let Some(where_) = rest[end + 1..].trim().strip_prefix("WHERE ") {
+        exclude.where_ = Some(Expr::Raw(strip_parens(where_).to_string()));
+    }
+    Ok(exclude)
+}
+
+fn parse_exclude_element(s: &str) -> Option<schema::ExcludeElement> {
+    let (column, operator) = s.rsplit_once(" WITH ")?;
+    Some(schema::ExcludeElement {
+        column: unquote(column).to_string(),
+        operator: operator.trim().to_string(),
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct Comment {
+    pub table_name: String,
+    /// `None` for a comment on the table itself.
+    pub column_name: Option<String>,
+    pub description: String,
+}
+
+async fn query_comments(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<Comment>> {
+    let s = include_str!("sql/query_comments.sql");
+    Ok(sqlx::query_as::<_, Comment>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?)
+}
+
+#[derive(sqlx::FromRow)]
+struct PrimaryKeyColumn {
+    pub table_name: String,
+    pub constraint_name: String,
+    pub column_name: String,
+}
+
+async fn query_primary_keys(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<PrimaryKeyColumn>> {
+    let s = include_str!("sql/query_primary_keys.sql");
+    Ok(sqlx::query_as::<_, PrimaryKeyColumn>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?)
+}
+
+#[derive(sqlx::FromRow)]
+struct IndexDefinition {
+    pub table_name: String,
+    pub index_name: String,
+    pub columns: Vec<String>,
+}
+
+async fn query_indexes(conn: &mut PgConnection, schema_name: &str) -> Result<Vec<IndexDefinition>> {
+    let s = include_str!("sql/query_indexes.sql");
+    Ok(sqlx::query_as::<_, IndexDefinition>(s)
+        .bind(schema_name)
+        .fetch_all(conn)
+        .await?)
+}
+
 impl TryInto<Column> for SchemaColumn {
     type Error = Error;
 
@@ -83,15 +391,43 @@ impl TryInto<Column> for SchemaColumn {
             "numeric" if self.numeric_precision.is_some() && self.numeric_scale.is_some() => {
                 Numeric(self.numeric_precision.unwrap() as u8, self.numeric_scale.unwrap() as u8)
             }
+            "character varying" if self.character_maximum_length.is_some() => {
+                Varchar(self.character_maximum_length.unwrap() as u32)
+            }
+            "character" => Char(self.character_maximum_length.unwrap_or(1) as u32),
+            "bit" => Bit(self.character_maximum_length.unwrap_or(1) as u32),
+            // 6 is the default precision, which is read as the plain timestamp types
+            "timestamp with time zone" if self.datetime_precision.is_some_and(|p| p != 6) => {
+                TimestampTz(self.datetime_precision.unwrap() as u8)
+            }
+            "timestamp without time zone" if self.datetime_precision.is_some_and(|p| p != 6) => {
+                Timestamp(self.datetime_precision.unwrap() as u8)
+            }
+            "interval" if self.interval_type.is_some() => {
+                Interval(self.interval_type.unwrap().to_lowercase())
+            }
+            "USER-DEFINED" => schema::Type::from_str(&self.udt_name)?,
             z => schema::Type::from_str(z)?,
         };
+        let generated = if self.is_identity == "YES" {
+            Some(schema::Generated::Identity {
+                always: self.identity_generation.as_deref() == Some("ALWAYS"),
+            })
+        } else if self.is_generated == "ALWAYS" {
+            let expr = self.generation_expression.ok_or_else(|| anyhow!("Generated column {} has no expression.", self.column_name))?;
+            Some(schema::Generated::Stored(Expr::Raw(expr)))
+        } else {
+            None
+        };
         Ok(Column {
             name: self.column_name.clone(),
             typ,
             nullable,
             primary_key: false,
-            default: None,
+            default: self.column_default.map(Expr::Raw),
             constraint: None,
+            generated,
+            comment: None,
         })
     }
 }
@@ -111,6 +447,9 @@ impl FromPostgres for Schema {
                     name: table_name,
                     columns,
                     indexes: vec![],
+                    primary_key: None,
+                    constraints: vec![],
+                    comment: None,
                 })
             })
             .collect::<Result<Vec<_>, Error>>()?;
@@ -118,11 +457,58 @@ impl FromPostgres for Schema {
         let constraints = query_constraints(conn, schema_name).await?;
         for fk in constraints {
             let table = tables.iter_mut().find(|t| t.name == fk.table_name).expect("Constraint for unknown table.");
-            let column = table.columns.iter_mut().find(|c| c.name == fk.column_name).expect("Constraint for unknown column.");
-            column.constraint = Some(schema::Constraint::ForeignKey(schema::ForeignKey {
-                table: fk.foreign_table_name,
-                columns: vec![fk.foreign_column_name],
-            }));
+            let constraint = schema::Constraint::ForeignKey(fk.to_foreign_key(schema_name));
+            // modeled on the table, so the real constraint name is kept for altering or dropping it.
+            table.constraints.push(schema::TableConstraint {
+                name: fk.constraint_name,
+                columns: fk.columns,
+                constraint,
+            });
+        }
+
+        let table_constraints = query_table_constraints(conn, schema_name).await?;
+        for constraint in table_constraints {
+            let table = tables.iter_mut().find(|t| t.name == constraint.table_name).expect("Constraint for unknown table.");
+            table.constraints.push(constraint.try_into()?);
+        }
+
+        let primary_keys = query_primary_keys(conn, schema_name).await?;
+        for pk in primary_keys {
+            let table = tables.iter_mut().find(|t| t.name == pk.table_name).expect("Primary key for unknown table.");
+            if let Some(column) = table.columns.iter_mut().find(|c| c.name == pk.column_name) {
+                column.primary_key = true;
+            }
+            table.primary_key
+                .get_or_insert_with(|| schema::PrimaryKey {
+                    name: Some(pk.constraint_name),
+                    columns: vec![],
+                })
+                .columns
+                .push(pk.column_name);
+        }
+
+        let indexes = query_indexes(conn, schema_name).await?;
+        for index in indexes {
+            let table = tables.iter_mut().find(|t| t.name == index.table_name).expect("Index for unknown table.");
+            table.indexes.push(schema::Index {
+                name: index.index_name,
+                columns: index.columns,
+            });
+        }
+
+        let comments = query_comments(conn, schema_name).await?;
+        for comment in comments {
+            let Some(table) = tables.iter_mut().find(|t| t.name == comment.table_name) else {
+                continue;
+            };
+            match comment.column_name {
+                Some(column_name) => {
+                    if let Some(column) = table.columns.iter_mut().find(|c| c.name == column_name) {
+                        column.comment = Some(comment.description);
+                    }
+                }
+                None => table.comment = Some(comment.description),
+            }
         }
 
         // Degenerate case but you can have tables with no columns...
@@ -136,12 +522,58 @@ impl FromPostgres for Schema {
                 name,
                 columns: vec![],
                 indexes: vec![],
+                primary_key: None,
+                constraints: vec![],
+                comment: None,
             })
         }
-        Ok(Schema { tables })
+        let enums = query_enums(conn, schema_name).await?;
+        for column in tables.iter_mut().flat_map(|t| t.columns.iter_mut()) {
+            resolve_enum_type(&mut column.typ, &enums);
+        }
+        let views = query_views(conn, schema_name).await?;
+        let sequences = query_sequences(conn, schema_name).await?;
+        Ok(Schema { tables, enums, views, sequences })
     }
 }
 
+/// Replace the queries of `schema`'s views with the definitions Postgres stores for them, so they
+/// compare equal to the views read from the database. Postgres rewrites view definitions, e.g.
+/// qualifying columns and adding parentheses and casts. A view whose query can't run yet, e.g.
+/// because it reads a column the migration adds, is left as it is.
+pub async fn normalize_views(conn: &mut PgConnection, schema: &mut Schema) -> Result<()> {
+    let mut tx = conn.begin().await?;
+    for view in &mut schema.views {
+        let query = view.query.to_sql(Dialect::Postgres);
+        sqlx::raw_sql("SAVEPOINT sqlmo_normalize").execute(&mut *tx).await?;
+        let create = format!("CREATE TEMPORARY VIEW sqlmo_normalize AS {query}");
+        if sqlx::raw_sql(&create).execute(&mut *tx).await.is_ok() {
+            let definition: String =
+                sqlx::query_scalar("SELECT pg_get_viewdef('pg_temp.sqlmo_normalize'::regclass)")
+                    .fetch_one(&mut *tx)
+                    .await?;
+            let definition = definition.trim().trim_end_matches(';');
+            view.query = schema::ViewQuery::Raw(definition.to_string());
+        }
+        sqlx::raw_sql("ROLLBACK TO SAVEPOINT sqlmo_normalize").execute(&mut *tx).await?;
+    }
+    tx.rollback().await?;
+    Ok(())
+}
+
+/// Compare the tables in `schema_name` to `desired`. See `sqlmo::migrate::check_drift`.
+pub async fn check_drift(
+    conn: &mut PgConnection,
+    schema_name: &str,
+    mut desired: Schema,
+    options: &DriftOptions,
+) -> Result<DriftReport> {
+    let database = Schema::try_from_postgres(conn, schema_name).await?;
+    desired.default_schema(schema_name);
+    normalize_views(conn, &mut desired).await?;
+    sqlmo::migrate::check_drift(database, desired, options)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -156,6 +588,15 @@ mod test {
             data_type: "numeric".to_string(),
             numeric_precision: Some(10),
             numeric_scale: Some(2),
+            character_maximum_length: None,
+            datetime_precision: None,
+            interval_type: None,
+            is_identity: "NO".to_string(),
+            identity_generation: None,
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            column_default: None,
+            udt_name: "numeric".to_string(),
             inner_type: None,
         };
         let column: Column = c.try_into().unwrap();
@@ -172,9 +613,145 @@ mod test {
             data_type: "integer".to_string(),
             numeric_precision: Some(32),
             numeric_scale: Some(0),
+            character_maximum_length: None,
+            datetime_precision: None,
+            interval_type: None,
+            is_identity: "NO".to_string(),
+            identity_generation: None,
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            column_default: Some("0".to_string()),
+            udt_name: "int4".to_string(),
             inner_type: None,
         };
         let column: Column = c.try_into().unwrap();
         assert_eq!(column.typ, schema::Type::I32);
+        assert_eq!(column.default, Some(Expr::Raw("0".to_string())));
+    }
+
+    #[test]
+    fn test_enum() {
+        let c = SchemaColumn {
+            table_name: "foo".to_string(),
+            column_name: "bar".to_string(),
+            ordinal_position: 1,
+            is_nullable: "YES".to_string(),
+            data_type: "USER-DEFINED".to_string(),
+            numeric_precision: None,
+            numeric_scale: None,
+            character_maximum_length: None,
+            datetime_precision: None,
+            interval_type: None,
+            is_identity: "NO".to_string(),
+            identity_generation: None,
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            column_default: None,
+            udt_name: "mood".to_string(),
+            inner_type: None,
+        };
+        let mut column: Column = c.try_into().unwrap();
+        resolve_enum_type(&mut column.typ, &[schema::Enum::new("mood", &["happy", "sad"])]);
+        assert_eq!(column.typ, schema::Type::Enum("mood".to_string()));
+    }
+
+    #[test]
+    fn test_varchar() {
+        let c = SchemaColumn {
+            table_name: "foo".to_string(),
+            column_name: "bar".to_string(),
+            ordinal_position: 1,
+            is_nullable: "NO".to_string(),
+            data_type: "character varying".to_string(),
+            numeric_precision: None,
+            numeric_scale: None,
+            character_maximum_length: Some(64),
+            datetime_precision: None,
+            interval_type: None,
+            is_identity: "NO".to_string(),
+            identity_generation: None,
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            column_default: None,
+            udt_name: "varchar".to_string(),
+            inner_type: None,
+        };
+        let column: Column = c.try_into().unwrap();
+        assert_eq!(column.typ, schema::Type::Varchar(64));
+    }
+
+    #[test]
+    fn test_identity() {
+        let c = SchemaColumn {
+            table_name: "foo".to_string(),
+            column_name: "id".to_string(),
+            ordinal_position: 1,
+            is_nullable: "NO".to_string(),
+            data_type: "bigint".to_string(),
+            numeric_precision: Some(64),
+            numeric_scale: Some(0),
+            character_maximum_length: None,
+            datetime_precision: None,
+            interval_type: None,
+            is_identity: "YES".to_string(),
+            identity_generation: Some("BY DEFAULT".to_string()),
+            is_generated: "NEVER".to_string(),
+            generation_expression: None,
+            column_default: None,
+            udt_name: "int8".to_string(),
+            inner_type: None,
+        };
+        let column: Column = c.try_into().unwrap();
+        assert_eq!(column.generated, Some(schema::Generated::Identity { always: false }));
+    }
+
+    #[test]
+    fn test_table_constraints() {
+        let c = TableConstraint {
+            constraint_name: "price_positive".to_string(),
+            table_name: "product".to_string(),
+            constraint_type: "c".to_string(),
+            columns: vec!["price".to_string()],
+            definition: "CHECK ((price > 0)) NOT VALID".to_string(),
+        };
+        let c: schema::TableConstraint = c.try_into().unwrap();
+        assert_eq!(c.constraint, schema::Constraint::Check(Expr::Raw("(price > 0)".to_string())));
+
+        let c = TableConstraint {
+            constraint_name: "no_overlap".to_string(),
+            table_name: "booking".to_string(),
+            constraint_type: "x".to_string(),
+            columns: vec!["room".to_string(), "during".to_string()],
+            definition: r#"EXCLUDE USING gist (room WITH =, "during" WITH &&) WHERE ((NOT cancelled))"#.to_string(),
+        };
+        let c: schema::TableConstraint = c.try_into().unwrap();
+        let expected = schema::Exclude::new("gist")
+            .element("room", "=")
+            .element("during", "&&")
+            .where_(Expr::Raw("(NOT cancelled)".to_string()));
+        assert_eq!(c.constraint, schema::Constraint::Exclude(expected));
+    }
+
+    #[test]
+    fn test_foreign_key() {
+        let fk = ForeignKey {
+            constraint_name: "user_team_id_fkey".to_string(),
+            table_name: "user".to_string(),
+            columns: vec!["team_id".to_string()],
+            foreign_table_schema: "auth".to_string(),
+            foreign_table_name: "team".to_string(),
+            foreign_columns: vec!["id".to_string()],
+            delete_action: "c".to_string(),
+            update_action: "a".to_string(),
+            match_type: "s".to_string(),
+            deferrable: true,
+            deferred: true,
+        };
+        let expected = schema::ForeignKey::new("team", &["id"])
+            .schema("auth")
+            .on_delete(schema::ReferentialAction::Cascade)
+            .deferrable(schema::Deferrable::InitiallyDeferred);
+        assert_eq!(fk.to_foreign_key("public"), expected);
+        assert_eq!(fk.to_foreign_key("auth").schema, None);
     }
 }
\ No newline at end of file
@@ -6,6 +6,15 @@ SELECT
 	data_type,
 	numeric_precision,
 	numeric_scale,
+	character_maximum_length,
+	datetime_precision,
+	interval_type,
+	is_identity,
+	identity_generation,
+	is_generated,
+	generation_expression,
+	column_default,
+	udt_name,
 	CASE WHEN data_type = 'ARRAY' THEN
 		substr(udt_name, 2)
 	END AS inner_type
@@ -13,6 +22,16 @@ FROM
 	information_schema.columns
 WHERE
 	table_schema = $1
+	-- columns of views are part of their definition
+	AND table_name IN (
+		SELECT
+			table_name
+		FROM
+			information_schema.tables
+		WHERE
+			table_schema = $1
+			AND table_type = 'BASE TABLE'
+	)
 ORDER BY
 	table_name,
 	ordinal_position
@@ -0,0 +1,15 @@
+SELECT
+	c.relname AS table_name,
+	a.attname AS column_name,
+	d.description
+FROM
+	pg_description d
+	JOIN pg_class c ON c.oid = d.objoid
+		AND d.classoid = 'pg_class'::regclass
+	JOIN pg_namespace n ON n.oid = c.relnamespace
+	LEFT JOIN pg_attribute a ON a.attrelid = c.oid
+		AND a.attnum = d.objsubid
+WHERE
+	n.nspname = $1
+	AND c.relkind IN ('r', 'p')
+	AND (d.objsubid = 0 OR a.attname IS NOT NULL)
@@ -1,16 +1,32 @@
 SELECT
-    tc.table_schema,
-    tc.constraint_name,
-    tc.table_name,
-    kcu.column_name,
-    ccu.table_schema AS foreign_table_schema,
-    ccu.table_name AS foreign_table_name,
-    ccu.column_name AS foreign_column_name
-FROM information_schema.table_constraints AS tc
-JOIN information_schema.key_column_usage AS kcu
-    ON tc.constraint_name = kcu.constraint_name
-    AND tc.table_schema = kcu.table_schema
-JOIN information_schema.constraint_column_usage AS ccu
-    ON ccu.constraint_name = tc.constraint_name
-WHERE tc.constraint_type = 'FOREIGN KEY'
-    AND tc.table_schema=$1;
\ No newline at end of file
+    con.conname AS constraint_name,
+    cl.relname AS table_name,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) AS columns,
+    fns.nspname AS foreign_table_schema,
+    fcl.relname AS foreign_table_name,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(con.confkey) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = con.confrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) AS foreign_columns,
+    con.confdeltype::text AS delete_action,
+    con.confupdtype::text AS update_action,
+    con.confmatchtype::text AS match_type,
+    con.condeferrable AS deferrable,
+    con.condeferred AS deferred
+FROM pg_constraint con
+JOIN pg_class cl ON cl.oid = con.conrelid
+JOIN pg_namespace ns ON ns.oid = cl.relnamespace
+JOIN pg_class fcl ON fcl.oid = con.confrelid
+JOIN pg_namespace fns ON fns.oid = fcl.relnamespace
+WHERE con.contype = 'f'
+    AND ns.nspname = $1
+ORDER BY
+    cl.relname,
+    con.conname
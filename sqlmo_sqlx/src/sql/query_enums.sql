@@ -0,0 +1,14 @@
+SELECT
+    t.typname::text AS name,
+    ARRAY(
+        SELECT e.enumlabel::text
+        FROM pg_enum e
+        WHERE e.enumtypid = t.oid
+        ORDER BY e.enumsortorder
+    ) AS variants
+FROM pg_type t
+JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE t.typtype = 'e'
+    AND n.nspname = $1
+ORDER BY
+    t.typname
@@ -0,0 +1,25 @@
+SELECT
+    t.relname AS table_name,
+    i.relname AS index_name,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(ix.indkey::int2[]) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = ix.indrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) AS columns
+FROM pg_index ix
+JOIN pg_class i ON i.oid = ix.indexrelid
+JOIN pg_class t ON t.oid = ix.indrelid
+JOIN pg_namespace ns ON ns.oid = t.relnamespace
+WHERE ns.nspname = $1
+    AND t.relkind IN ('r', 'p')
+    -- indexes backing primary keys, unique and exclusion constraints are read as constraints
+    AND NOT EXISTS (
+        SELECT 1
+        FROM pg_constraint con
+        WHERE con.conindid = ix.indexrelid
+            AND con.contype IN ('p', 'u', 'x')
+    )
+ORDER BY
+    t.relname,
+    i.relname
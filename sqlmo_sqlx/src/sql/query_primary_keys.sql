@@ -0,0 +1,14 @@
+SELECT
+    tc.table_name,
+    tc.constraint_name,
+    kcu.column_name
+FROM information_schema.table_constraints AS tc
+JOIN information_schema.key_column_usage AS kcu
+    ON tc.constraint_name = kcu.constraint_name
+    AND tc.table_schema = kcu.table_schema
+    AND tc.table_name = kcu.table_name
+WHERE tc.constraint_type = 'PRIMARY KEY'
+    AND tc.table_schema = $1
+ORDER BY
+    tc.table_name,
+    kcu.ordinal_position
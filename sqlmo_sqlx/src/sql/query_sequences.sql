@@ -0,0 +1,28 @@
+SELECT
+	s.sequence_name AS name,
+	s.data_type,
+	s.start_value::bigint AS start_value,
+	s.increment::bigint AS increment,
+	s.minimum_value::bigint AS minimum_value,
+	s.maximum_value::bigint AS maximum_value,
+	s.cycle_option = 'YES' AS cycle,
+	t.relname AS owned_by_table,
+	a.attname AS owned_by_column
+FROM
+	information_schema.sequences s
+	JOIN pg_namespace n ON n.nspname = s.sequence_schema
+	JOIN pg_class c ON c.relname = s.sequence_name
+		AND c.relnamespace = n.oid
+	LEFT JOIN pg_depend d ON d.objid = c.oid
+		AND d.classid = 'pg_class'::regclass
+		AND d.refclassid = 'pg_class'::regclass
+		AND d.deptype IN ('a', 'i')
+	LEFT JOIN pg_class t ON t.oid = d.refobjid
+	LEFT JOIN pg_attribute a ON a.attrelid = t.oid
+		AND a.attnum = d.refobjsubid
+WHERE
+	s.sequence_schema = $1
+	-- identity sequences are part of their column
+	AND (d.deptype IS NULL OR d.deptype = 'a')
+ORDER BY
+	s.sequence_name
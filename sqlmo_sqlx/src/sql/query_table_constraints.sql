@@ -0,0 +1,19 @@
+SELECT
+    con.conname AS constraint_name,
+    cl.relname AS table_name,
+    con.contype::text AS constraint_type,
+    ARRAY(
+        SELECT a.attname::text
+        FROM unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+        JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+        ORDER BY k.ord
+    ) AS columns,
+    pg_get_constraintdef(con.oid) AS definition
+FROM pg_constraint con
+JOIN pg_class cl ON cl.oid = con.conrelid
+JOIN pg_namespace ns ON ns.oid = cl.relnamespace
+WHERE con.contype IN ('u', 'c', 'x')
+    AND ns.nspname = $1
+ORDER BY
+    cl.relname,
+    con.conname
@@ -4,4 +4,6 @@ SELECT
 FROM
 	information_schema.tables
 WHERE
-	table_schema = $1
\ No newline at end of file
+	table_schema = $1
+	-- views are read separately
+	AND table_type = 'BASE TABLE'
\ No newline at end of file
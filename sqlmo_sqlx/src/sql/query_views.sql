@@ -0,0 +1,19 @@
+SELECT
+	viewname AS name,
+	definition,
+	false AS materialized
+FROM
+	pg_views
+WHERE
+	schemaname = $1
+UNION ALL
+SELECT
+	matviewname AS name,
+	definition,
+	true AS materialized
+FROM
+	pg_matviews
+WHERE
+	schemaname = $1
+ORDER BY
+	name
@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlmo::migrate::{DriftOptions, MigrationHistory};
+use sqlmo::schema::{ForeignKey, Index, View};
+use sqlmo::{Column, Constraint, Expr, Migration, MigrationOptions, Schema, Table, Type};
+use sqlmo_sqlx::{
+    applied_migrations, apply_migration, check_drift, normalize_views, ApplyOptions, FromPostgres,
+};
+use sqlx::{Connection, PgConnection};
+
+#[test]
+#[ignore = "needs a Postgres database at DATABASE_URL"]
+fn test_apply_migration() -> Result<()> {
+    let url = std::env::var("DATABASE_URL")?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut conn = PgConnection::connect(&url).await?;
+        sqlx::raw_sql(
+            "DROP SCHEMA IF EXISTS sqlmo_apply CASCADE; CREATE SCHEMA sqlmo_apply",
+        )
+        .execute(&mut conn)
+        .await?;
+        let current = Schema::try_from_postgres(&mut conn, "sqlmo_apply").await?;
+        let mut desired = Schema {
+            tables: vec![Table::new("user").column(Column::new("id", Type::I32).primary_key())],
+            ..Schema::default()
+        };
+        desired.default_schema("sqlmo_apply");
+        let options = MigrationOptions {
+            default_schema: Some("sqlmo_apply".to_string()),
+            ..MigrationOptions::default()
+        };
+        let migration: Migration = current.migrate_to(desired.clone(), &options)?;
+
+        let history = MigrationHistory::default().schema("sqlmo_apply");
+        let apply = ApplyOptions {
+            history: history.clone(),
+            ..ApplyOptions::default()
+        }
+        .lock_timeout(Duration::from_secs(5));
+        let applied = apply_migration(&mut conn, &migration, &apply).await?;
+        assert_eq!(applied.name, "create_user");
+        // applying it again is a no-op
+        let again = apply_migration(&mut conn, &migration, &apply).await?;
+        assert_eq!(again, applied);
+        assert_eq!(applied_migrations(&mut conn, &history).await?.len(), 1);
+
+        let current = Schema::try_from_postgres(&mut conn, "sqlmo_apply").await?;
+        let tables = current.tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+        assert!(tables.contains(&"user"));
+        assert!(current.migrate_to(desired.clone(), &options)?.is_empty());
+        let drift_options = DriftOptions::default();
+        let drift = check_drift(&mut conn, "sqlmo_apply", desired, &drift_options).await?;
+        drift.into_result()?;
+
+        sqlx::raw_sql("DROP SCHEMA sqlmo_apply CASCADE")
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    })
+}
+
+#[test]
+#[ignore = "needs a Postgres database at DATABASE_URL"]
+fn test_apply_online_migration() -> Result<()> {
+    let url = std::env::var("DATABASE_URL")?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut conn = PgConnection::connect(&url).await?;
+        sqlx::raw_sql(
+            r#"DROP SCHEMA IF EXISTS sqlmo_online CASCADE;
+            CREATE SCHEMA sqlmo_online;
+            CREATE TABLE sqlmo_online.team (id integer PRIMARY KEY);
+            CREATE TABLE sqlmo_online."user" (id integer PRIMARY KEY, age integer, team_id integer);
+            INSERT INTO sqlmo_online.team VALUES (1);
+            INSERT INTO sqlmo_online."user" VALUES (1, 30, 1), (2, 40, NULL);"#,
+        )
+        .execute(&mut conn)
+        .await?;
+        let current = Schema::try_from_postgres(&mut conn, "sqlmo_online").await?;
+        let mut desired = current.clone();
+        let user = desired.tables.iter_mut().find(|t| t.name == "user").unwrap();
+        for column in &mut user.columns {
+            match column.name.as_str() {
+                "age" => {
+                    column.typ = Type::I64;
+                    column.nullable = false;
+                }
+                "team_id" => {
+                    let fk = ForeignKey::new("team", &["id"]).schema("sqlmo_online");
+                    column.constraint = Some(Constraint::ForeignKey(fk));
+                }
+                _ => {}
+            }
+        }
+        user.columns.push(Column::new("score", Type::I64).not_null());
+        user.indexes.push(Index {
+            name: "user_team_id_idx".to_string(),
+            columns: vec!["team_id".to_string()],
+        });
+        let options = MigrationOptions {
+            default_schema: Some("sqlmo_online".to_string()),
+            online: true,
+            backfill_batch_size: Some(1),
+            ..MigrationOptions::default()
+        }
+        .backfill("user", "score", Expr::Raw(r#""id" * 10"#.to_string()));
+        let migration = current.migrate_to(desired.clone(), &options)?;
+        let apply = ApplyOptions {
+            history: MigrationHistory::default().schema("sqlmo_online"),
+            ..ApplyOptions::default()
+        };
+        apply_migration(&mut conn, &migration, &apply).await?;
+
+        let current = Schema::try_from_postgres(&mut conn, "sqlmo_online").await?;
+        assert!(current.migrate_to(desired, &options)?.is_empty());
+        let ages: Vec<i64> =
+            sqlx::query_scalar(r#"SELECT age FROM sqlmo_online."user" ORDER BY id"#)
+                .fetch_all(&mut conn)
+                .await?;
+        assert_eq!(ages, vec![30, 40]);
+        let scores: Vec<i64> =
+            sqlx::query_scalar(r#"SELECT score FROM sqlmo_online."user" ORDER BY id"#)
+                .fetch_all(&mut conn)
+                .await?;
+        assert_eq!(scores, vec![10, 20]);
+
+        sqlx::raw_sql("DROP SCHEMA sqlmo_online CASCADE")
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    })
+}
+
+#[test]
+#[ignore = "needs a Postgres database at DATABASE_URL"]
+fn test_views_are_not_tables() -> Result<()> {
+    let url = std::env::var("DATABASE_URL")?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut conn = PgConnection::connect(&url).await?;
+        sqlx::raw_sql(
+            r#"DROP SCHEMA IF EXISTS sqlmo_views CASCADE;
+            CREATE SCHEMA sqlmo_views;
+            CREATE TABLE sqlmo_views."user" (id integer PRIMARY KEY, age integer);
+            CREATE VIEW sqlmo_views.adult AS SELECT id FROM sqlmo_views."user" WHERE age >= 18;
+            CREATE MATERIALIZED VIEW sqlmo_views.user_count AS
+                SELECT count(*) FROM sqlmo_views."user";"#,
+        )
+        .execute(&mut conn)
+        .await?;
+        let current = Schema::try_from_postgres(&mut conn, "sqlmo_views").await?;
+        let tables = current.tables.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(tables, vec!["user"]);
+        let views = current.views.iter().map(|v| v.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(views, vec!["adult", "user_count"]);
+
+        let options = MigrationOptions {
+            default_schema: Some("sqlmo_views".to_string()),
+            ..MigrationOptions::default()
+        };
+        assert!(current.clone().migrate_to(current.clone(), &options)?.is_empty());
+
+        // the views as written compare equal once Postgres has rewritten them
+        let mut desired = current.clone();
+        desired.views = vec![
+            View::new("adult", r#"SELECT id FROM sqlmo_views."user" WHERE age >= 18"#)
+                .schema("sqlmo_views"),
+            View::new("user_count", r#"SELECT count(*) FROM sqlmo_views."user""#)
+                .schema("sqlmo_views")
+                .materialized(),
+        ];
+        assert!(!current.clone().migrate_to(desired.clone(), &options)?.is_empty());
+        let drift_options = DriftOptions::default();
+        let drift = check_drift(&mut conn, "sqlmo_views", desired.clone(), &drift_options).await?;
+        drift.into_result()?;
+        normalize_views(&mut conn, &mut desired).await?;
+        assert!(current.migrate_to(desired, &options)?.is_empty());
+
+        sqlx::raw_sql("DROP SCHEMA sqlmo_views CASCADE")
+            .execute(&mut conn)
+            .await?;
+        Ok(())
+    })
+}
@@ -0,0 +1,167 @@
+use anyhow::Result;
+use sqlmo::migrate::Statement;
+use sqlmo::schema::{ForeignKey, Index, ReferentialAction};
+use sqlmo::{
+    migrate, Column, Constraint, Dialect, Expr, MigrationOptions, Schema, Table, ToSql, Type,
+};
+use sqlx::{Connection, Row, SqliteConnection};
+
+/// Run each statement in its own transaction, unless it can't run in one.
+async fn run(conn: &mut SqliteConnection, statements: &[Statement]) -> Result<()> {
+    for statement in statements {
+        let sql = statement.to_sql(Dialect::Sqlite);
+        if !statement.is_transactional() {
+            sqlx::raw_sql(&sql).execute(&mut *conn).await?;
+            continue;
+        }
+        let mut tx = conn.begin().await?;
+        sqlx::raw_sql(&sql).execute(&mut *tx).await?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_rebuild_sqlite_table() -> Result<()> {
+    let column = |name: &str, typ: Type, nullable: bool| Column {
+        nullable,
+        ..Column::new(name, typ)
+    };
+    let id = Column::new("id", Type::I64).primary_key();
+    let user_id = ForeignKey::new("user", &["id"]).on_delete(ReferentialAction::Cascade);
+    let membership = Table::new("membership")
+        .column(column("user_id", Type::I64, false).constraint(Constraint::ForeignKey(user_id)));
+    let current = Schema {
+        tables: vec![
+            Table::new("user")
+                .column(id.clone())
+                .column(column("age", Type::I32, true)),
+            membership.clone(),
+        ],
+        ..Schema::default()
+    };
+    let desired = Schema {
+        tables: vec![
+            Table::new("user")
+                .column(id)
+                .column(column("age", Type::F64, false))
+                .column(column("score", Type::I32, false))
+                .index(Index {
+                    name: "user_score_idx".to_string(),
+                    columns: vec!["score".to_string()],
+                }),
+            membership,
+        ],
+        ..Schema::default()
+    };
+    let options = MigrationOptions {
+        dialect: Some(Dialect::Sqlite),
+        ..MigrationOptions::default()
+    }
+    .backfill("user", "score", Expr::Raw(r#""id" * 10"#.to_string()));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+        let create = migrate(Schema::default(), current.clone(), &options)?;
+        run(&mut conn, &create.statements).await?;
+        sqlx::raw_sql(
+            r#"INSERT INTO "user" ("id", "age") VALUES (1, 30), (2, 40);
+            INSERT INTO "membership" ("user_id") VALUES (1), (2)"#,
+        )
+        .execute(&mut conn)
+        .await?;
+
+        let migration = migrate(current, desired, &options)?;
+        assert!(migration
+            .statements
+            .iter()
+            .any(|s| matches!(s, Statement::RebuildTable(_))));
+        run(&mut conn, &migration.statements).await?;
+        let users = sqlx::query(r#"SELECT "id", "age", "score" FROM "user" ORDER BY "id""#)
+            .fetch_all(&mut conn)
+            .await?
+            .iter()
+            .map(|r| (r.get::<i64, _>(0), r.get::<f64, _>(1), r.get::<i64, _>(2)))
+            .collect::<Vec<_>>();
+        assert_eq!(users, vec![(1, 30.0, 10), (2, 40.0, 20)]);
+        // dropping the old table didn't cascade to the rows referencing it
+        let count = || sqlx::query_scalar::<_, i64>(r#"SELECT count(*) FROM "membership""#);
+        assert_eq!(count().fetch_one(&mut conn).await?, 2);
+        let index = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'user'",
+        )
+        .fetch_all(&mut conn)
+        .await?;
+        assert_eq!(index, vec!["user_score_idx".to_string()]);
+        let enabled = sqlx::query_scalar::<_, i64>("PRAGMA foreign_keys")
+            .fetch_one(&mut conn)
+            .await?;
+        assert_eq!(enabled, 1);
+
+        run(&mut conn, &migration.down).await?;
+        let users = sqlx::query(r#"SELECT * FROM "user" ORDER BY "id""#)
+            .fetch_all(&mut conn)
+            .await?;
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].columns().len(), 2);
+        assert_eq!(count().fetch_one(&mut conn).await?, 2);
+        Ok(())
+    })
+}
+
+#[test]
+fn test_rebuild_sqlite_table_foreign_key_violation() -> Result<()> {
+    let user_id = ForeignKey::new("user", &["id"]);
+    let membership = Table::new("membership").column(
+        Column::new("user_id", Type::I64)
+            .not_null()
+            .constraint(Constraint::ForeignKey(user_id)),
+    );
+    let user = |age: Type| {
+        Table::new("user")
+            .column(Column::new("id", Type::I64).primary_key())
+            .column(Column::new("age", age))
+    };
+    let current = Schema {
+        tables: vec![user(Type::I32), membership.clone()],
+        ..Schema::default()
+    };
+    let desired = Schema {
+        tables: vec![user(Type::F64), membership],
+        ..Schema::default()
+    };
+    let options = MigrationOptions {
+        dialect: Some(Dialect::Sqlite),
+        ..MigrationOptions::default()
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await?;
+        let create = migrate(Schema::default(), current.clone(), &options)?;
+        run(&mut conn, &create.statements).await?;
+        sqlx::raw_sql(
+            r#"PRAGMA foreign_keys = off;
+            INSERT INTO "membership" ("user_id") VALUES (1)"#,
+        )
+        .execute(&mut conn)
+        .await?;
+
+        let migration = migrate(current, desired, &options)?;
+        let err = run(&mut conn, &migration.statements).await.unwrap_err();
+        assert!(err.to_string().contains("CHECK constraint failed"), "{err}");
+        // the rebuild was rolled back
+        let tables = sqlx::query_scalar::<_, String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name",
+        )
+        .fetch_all(&mut conn)
+        .await?;
+        assert_eq!(tables, vec!["membership".to_string(), "user".to_string()]);
+        Ok(())
+    })
+}
@@ -1,51 +1,192 @@
 use std::collections::HashMap;
 
-use crate::query::{AlterTable, Update};
+use crate::query::{
+    AlterTable, Backfill, CommentOn, CommentTarget, Expr, LockTable, Pragma, RebuildTable, Select,
+    Update,
+};
 use anyhow::Result;
 
-use crate::query::AlterAction;
+use crate::query::{AlterAction, AlterColumnAction};
+use crate::query::{AlterEnum, CreateEnum, EnumValuePosition};
 use crate::query::CreateIndex;
 use crate::query::CreateTable;
 use crate::query::DropTable;
-use crate::schema::{Constraint, Schema};
+use crate::query::{CreateSchema, DropSchema};
+use crate::query::{DropEnum, DropIndex};
+use crate::query::{AlterSequence, CreateSequence, DropSequence};
+use crate::query::{CreateView, DropView, RefreshMaterializedView};
+use crate::query::{CreateTrigger, DropTrigger};
+use crate::schema::{
+    Column, Constraint, Conversion, ForeignKey, Generated, PrimaryKey, Schema, Sequence,
+    SequenceOptions, Table, TableConstraint, Type, View,
+};
+use crate::util::SqlExtension;
 use crate::{Dialect, ToSql};
-use topo_sort::{SortResults, TopoSort};
+
+mod drift;
+mod files;
+mod history;
+mod mysql;
+mod online;
+mod reverse;
+mod risk;
+mod sqlite;
+
+pub use drift::{check_drift, Drift, DriftOptions, DriftReport};
+pub use files::{MigrationLayout, MigrationWriter};
+pub use history::{checksum, AppliedMigration, MigrationHistory};
+pub use reverse::Irreversible;
+pub use risk::{LockLevel, RiskReport, Safety, StatementRisk};
 
 #[derive(Debug, Clone, Default)]
 pub struct MigrationOptions {
     pub debug: bool,
     pub allow_destructive: bool,
+    /// The schema of tables, enums, views and sequences that don't name one. Defaults to `public`.
+    pub default_schema: Option<String>,
+    /// Alter existing tables in steps that don't block reads and writes for long: constraints
+    /// are added `NOT VALID` and validated separately, `SET NOT NULL` is proven by a check
+    /// constraint first, indexes are created concurrently, and column types are changed by
+    /// backfilling a new column. Ignored for dialects other than Postgres. The steps have to
+    /// commit separately, as `apply_migration` in sqlmo_sqlx does.
+    pub online: bool,
+    /// Values for the existing rows of new `NOT NULL` columns, by schema, table and column name.
+    /// Takes precedence over the column's default.
+    pub backfill: HashMap<ColumnKey, Expr>,
+    /// Expressions converting the existing values of columns whose type changes, by schema, table
+    /// and column name. Takes precedence over the default cast, and is required to make a
+    /// conversion that has none, along with `allow_destructive`.
+    pub using: HashMap<ColumnKey, Expr>,
+    /// Backfill tables with an integer primary key this many keys at a time, committing each
+    /// batch. Only `apply_migration` in sqlmo_sqlx can run the batches, so `MigrationWriter`
+    /// rejects migrations with batched backfills.
+    pub backfill_batch_size: Option<u64>,
+    /// The database the migration is for. Defaults to Postgres. SQLite can only add, drop and
+    /// rename columns, so its other table changes rebuild the table.
+    pub dialect: Option<Dialect>,
+}
+
+/// A column by schema, table and column name. A schema of `None` is the default schema.
+pub type ColumnKey = (Option<String>, String, String);
+
+fn column_key(schema: Option<&str>, table: &str, column: &str) -> ColumnKey {
+    (schema.map(str::to_string), table.to_string(), column.to_string())
+}
+
+impl MigrationOptions {
+    /// Backfill `column` of `table` in the default schema.
+    pub fn backfill(self, table: &str, column: &str, value: impl Into<Expr>) -> Self {
+        self.backfill_in(None, table, column, value)
+    }
+
+    pub fn backfill_in(
+        mut self,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: impl Into<Expr>,
+    ) -> Self {
+        self.backfill.insert(column_key(schema, table, column), value.into());
+        self
+    }
+
+    /// Convert `column` of `table` in the default schema with `value`.
+    pub fn using(self, table: &str, column: &str, value: impl Into<Expr>) -> Self {
+        self.using_in(None, table, column, value)
+    }
+
+    pub fn using_in(
+        mut self,
+        schema: Option<&str>,
+        table: &str,
+        column: &str,
+        value: impl Into<Expr>,
+    ) -> Self {
+        self.using.insert(column_key(schema, table, column), value.into());
+        self
+    }
+}
+
+/// The expression given for `column` of the table identified by `key`.
+fn column_option<'a>(
+    options: &'a HashMap<ColumnKey, Expr>,
+    key: TableKey,
+    column: &str,
+    default_schema: &str,
+) -> Option<&'a Expr> {
+    options
+        .iter()
+        .find(|((s, t, c), _)| table_key(s, t, default_schema) == key && c == column)
+        .map(|(_, e)| e)
+}
+
+/// Tables are identified by schema and name.
+type TableKey<'a> = (&'a str, &'a str);
+
+/// The name, qualified with its schema if it has one.
+fn display_name(schema: &Option<String>, name: &str) -> String {
+    match schema {
+        Some(schema) => format!("{}.{}", schema, name),
+        None => name.to_string(),
+    }
+}
+
+fn table_key<'a>(schema: &'a Option<String>, name: &'a str, default: &'a str) -> TableKey<'a> {
+    (schema.as_deref().unwrap_or(default), name)
 }
 
 pub fn migrate(current: Schema, desired: Schema, options: &MigrationOptions) -> Result<Migration> {
+    let default_schema = options.default_schema.as_deref().unwrap_or("public");
+    let dialect = options.dialect.unwrap_or(Dialect::Postgres);
+    let online = options.online && dialect == Dialect::Postgres;
     let current_tables = current
         .tables
         .iter()
-        .map(|t| (&t.name, t))
+        .map(|t| (table_key(&t.schema, &t.name, default_schema), t))
         .collect::<HashMap<_, _>>();
     let desired_tables = desired
         .tables
         .iter()
-        .map(|t| (&t.name, t))
+        .map(|t| (table_key(&t.schema, &t.name, default_schema), t))
         .collect::<HashMap<_, _>>();
 
     let mut debug_results = vec![];
     let mut statements = Vec::new();
+    // MySQL declares the comments of new tables and columns with them
+    let inline_comments = options.dialect == Some(Dialect::Mysql);
+    // tables are compared in the order of their schema, so the statements come out the same
+    // every time
+    let is_current = |t: &&Table| {
+        current_tables.contains_key(&table_key(&t.schema, &t.name, default_schema))
+    };
+    let is_desired = |t: &&Table| {
+        desired_tables.contains_key(&table_key(&t.schema, &t.name, default_schema))
+    };
     // new tables
-    for (_name, table) in desired_tables
-        .iter()
-        .filter(|(name, _)| !current_tables.contains_key(*name))
-    {
-        let statement = Statement::CreateTable(CreateTable::from_table(table));
-        statements.push(statement);
+    for table in desired.tables.iter().filter(|t| !is_current(t)) {
+        let mut create = CreateTable::from_table(table);
+        create.enums = desired.enums_for(table);
+        statements.push(Statement::CreateTable(create));
+        if table.comment.is_some() && !inline_comments {
+            statements.push(Statement::CommentOn(
+                CommentOn::table(&table.name, table.comment.clone()).schema(table.schema.clone()),
+            ));
+        }
+        for column in table.columns.iter().filter(|c| c.comment.is_some() && !inline_comments) {
+            statements.push(Statement::CommentOn(
+                CommentOn::column(&table.name, column.clone()).schema(table.schema.clone()),
+            ));
+        }
+        for index in &table.indexes {
+            statements.push(Statement::CreateIndex(CreateIndex::from_index(table, index)));
+        }
     }
 
     // alter existing tables
-    for (name, desired_table) in desired_tables
-        .iter()
-        .filter(|(name, _)| current_tables.contains_key(*name))
-    {
-        let current_table = current_tables[name];
+    for desired_table in desired.tables.iter().filter(is_current) {
+        let key = table_key(&desired_table.schema, &desired_table.name, default_schema);
+        let name = &desired_table.name;
+        let current_table = current_tables[&key];
         let current_columns = current_table
             .columns
             .iter()
@@ -53,176 +194,996 @@ pub fn migrate(current: Schema, desired: Schema, options: &MigrationOptions) ->
             .collect::<HashMap<_, _>>();
         // add columns
         let mut actions = vec![];
+        let mut reseed = vec![];
+        let mut serials = vec![];
+        let mut comments = vec![];
+        if current_table.comment != desired_table.comment {
+            comments.push(CommentOn::table(&desired_table.name, desired_table.comment.clone()));
+        }
         for desired_column in desired_table.columns.iter() {
+            let current_column = current_columns.get(&desired_column.name);
+            let current_comment = current_column.and_then(|c| c.comment.as_ref());
+            let added_inline = current_column.is_none() && inline_comments;
+            if current_comment != desired_column.comment.as_ref() && !added_inline {
+                comments.push(CommentOn::column(&desired_table.name, desired_column.clone()));
+            }
             if let Some(current) = current_columns.get(&desired_column.name) {
+                match diff_generated(current, desired_column, &mut actions) {
+                    GeneratedDiff::Done => {}
+                    GeneratedDiff::AddedIdentity(serial) => {
+                        reseed.push(desired_column.name.clone());
+                        serials.extend(serial.map(|s| (desired_column.name.clone(), s)));
+                    }
+                    GeneratedDiff::Rebuild if options.allow_destructive => {
+                        actions.push(AlterAction::DropColumn {
+                            name: desired_column.name.clone(),
+                        });
+                        actions.push(AlterAction::AddColumn {
+                            column: desired_column.clone(),
+                        });
+                        continue;
+                    }
+                    GeneratedDiff::Rebuild => {
+                        debug_results.push(DebugResults::SkippedColumnRebuild {
+                            table: name.to_string(),
+                            column: desired_column.name.clone(),
+                        });
+                    }
+                }
                 if current.nullable != desired_column.nullable {
                     actions.push(AlterAction::set_nullable(
                         desired_column.name.clone(),
                         desired_column.nullable,
                     ));
                 }
-                if !desired_column.typ.lossy_eq(&current.typ) {
-                    actions.push(AlterAction::set_type(
-                        desired_column.name.clone(),
-                        desired_column.typ.clone(),
-                    ));
+                if !desired_column.typ.lossy_eq_in(&current.typ, dialect) {
+                    let conversion = current.typ.conversion_to(&desired_column.typ);
+                    let given =
+                        column_option(&options.using, key, &desired_column.name, default_schema);
+                    let using = match given {
+                        Some(using) => Some(using.clone()),
+                        None => current
+                            .typ
+                            .conversion_using(&desired_column.name, &desired_column.typ)
+                            .map(Expr::Raw),
+                    };
+                    // there's no cast, so only an expression the user gave can convert the values
+                    let skip = conversion == Conversion::Impossible
+                        && (given.is_none() || !options.allow_destructive);
+                    if conversion.is_risky() {
+                        debug_results.push(DebugResults::RiskyTypeConversion {
+                            table: name.to_string(),
+                            column: desired_column.name.clone(),
+                            from: current.typ.clone(),
+                            to: desired_column.typ.clone(),
+                            conversion,
+                            skipped: skip,
+                        });
+                    }
+                    if !skip {
+                        actions.push(AlterAction::AlterColumn {
+                            name: desired_column.name.clone(),
+                            action: AlterColumnAction::SetType {
+                                typ: desired_column.typ.clone(),
+                                using,
+                                // a given expression can change the values however it likes
+                                conversion: given.is_none().then_some(conversion),
+                            },
+                        });
+                    }
                 };
-                if desired_column.constraint.is_some() && current.constraint.is_none() {
-                    if let Some(c) = &desired_column.constraint {
-                        let name = desired_column.name.clone();
-                        actions.push(AlterAction::add_constraint(
-                            &desired_table.name,
-                            name,
-                            c.clone(),
+                match (&current.default, &desired_column.default) {
+                    (Some(current), Some(desired)) if desired.lossy_eq(current) => {}
+                    (_, Some(desired)) => {
+                        actions.push(AlterAction::set_default(
+                            desired_column.name.clone(),
+                            desired.clone(),
                         ));
                     }
+                    // serial columns report their sequence as the default; leave those alone.
+                    (Some(current), None) if !is_sequence_default(current) => {
+                        actions.push(AlterAction::drop_default(desired_column.name.clone()));
+                    }
+                    _ => {}
                 }
             } else {
+                let backfill =
+                    column_option(&options.backfill, key, &desired_column.name, default_schema);
+                // a default fills the existing rows, but a volatile one rewrites the table
+                let filled_by_default = desired_column.default.as_ref().is_some_and(|d| {
+                    backfill.is_none() && !(online && risk::is_volatile(d))
+                });
                 // add the column can be in 1 step if the column is nullable
-                if desired_column.nullable {
+                if desired_column.nullable
+                    || desired_column.generated.is_some()
+                    || filled_by_default
+                {
                     actions.push(AlterAction::AddColumn {
                         column: desired_column.clone(),
                     });
                 } else {
+                    let Some(value) = backfill.or(desired_column.default.as_ref()) else {
+                        debug_results.push(DebugResults::SkippedAddColumn {
+                            table: display_name(&desired_table.schema, name),
+                            column: desired_column.name.clone(),
+                        });
+                        // nor is its comment
+                        let column = &desired_column.name;
+                        comments.retain(|c| {
+                            !matches!(&c.target, CommentTarget::Column(c) if c.name == *column)
+                        });
+                        continue;
+                    };
                     let mut nullable = desired_column.clone();
                     nullable.nullable = true;
+                    nullable.default = None;
                     statements.push(Statement::AlterTable(AlterTable {
                         schema: desired_table.schema.clone(),
                         name: desired_table.name.clone(),
                         actions: vec![AlterAction::AddColumn { column: nullable }],
+                        enums: desired.enums_for(desired_table),
                     }));
-                    statements.push(Statement::Update(
-                        Update::new(name)
-                            .set(
-                                &desired_column.name,
-                                "/* TODO set a value before setting the column to null */",
-                            )
-                            .where_(crate::query::Where::raw("true")),
+                    // the default applies to rows inserted from here on
+                    if let Some(default) = &desired_column.default {
+                        statements.push(Statement::AlterTable(AlterTable {
+                            schema: desired_table.schema.clone(),
+                            name: desired_table.name.clone(),
+                            actions: vec![AlterAction::set_default(
+                                desired_column.name.clone(),
+                                default.clone(),
+                            )],
+                            enums: vec![],
+                        }));
+                    }
+                    let backfill = backfill_column(
+                        current_table,
+                        &desired_column.name,
+                        value.clone(),
+                        options.backfill_batch_size,
+                    );
+                    statements.push(Statement::Backfill(
+                        backfill.schema(desired_table.schema.clone()),
                     ));
                     statements.push(Statement::AlterTable(AlterTable {
                         schema: desired_table.schema.clone(),
                         name: desired_table.name.clone(),
                         actions: vec![AlterAction::AlterColumn {
                             name: desired_column.name.clone(),
-                            action: crate::query::AlterColumnAction::SetNullable(false),
+                            action: AlterColumnAction::SetNullable(false),
                         }],
+                        enums: vec![],
                     }));
                 }
             }
         }
-        if actions.is_empty() {
+        diff_constraints(current_table, desired_table, &mut actions);
+        let current_pkey = current_table.primary_key_columns();
+        let desired_pkey = desired_table.primary_key_columns();
+        if current_pkey != desired_pkey {
+            // drop first, so the remaining actions can alter the old primary key columns
+            if !current_pkey.is_empty() {
+                actions.insert(
+                    0,
+                    AlterAction::drop_primary_key(current_table.primary_key_name()),
+                );
+            }
+            if !desired_pkey.is_empty() {
+                actions.push(AlterAction::AddPrimaryKey(PrimaryKey {
+                    name: desired_table.primary_key.as_ref().and_then(|pk| pk.name.clone()),
+                    columns: desired_pkey,
+                }));
+            }
+        }
+        if actions.is_empty() && comments.is_empty() {
             debug_results.push(DebugResults::TablesIdentical(name.to_string()));
-        } else {
+        } else if !actions.is_empty() {
+            // the serial sequences are dropped before the identities replacing them are added,
+            // which then get their names
+            if !serials.is_empty() {
+                statements.push(Statement::AlterTable(AlterTable {
+                    schema: desired_table.schema.clone(),
+                    name: desired_table.name.clone(),
+                    actions: serials
+                        .iter()
+                        .map(|(column, _)| AlterAction::drop_default(column.clone()))
+                        .collect(),
+                    enums: vec![],
+                }));
+            }
+            for (_, (schema, name)) in serials {
+                statements.push(Statement::DropSequence(DropSequence { schema, name }));
+            }
             statements.push(Statement::AlterTable(AlterTable {
                 schema: desired_table.schema.clone(),
                 name: desired_table.name.clone(),
                 actions,
+                enums: desired.enums_for(desired_table),
             }));
+            // MySQL and SQLite continue from the existing rows on their own
+            for column in reseed.into_iter().filter(|_| dialect == Dialect::Postgres) {
+                statements.push(Statement::Select(reseed_identity(desired_table, &column)));
+            }
+        }
+        for comment in comments {
+            let comment = comment
+                .schema(desired_table.schema.clone())
+                .enums(desired.enums_for(desired_table));
+            statements.push(Statement::CommentOn(comment));
+        }
+        // indexes missing from the database are created, but like columns, extra ones are kept
+        for index in &desired_table.indexes {
+            if !current_table.indexes.iter().any(|i| i.name == index.name) {
+                statements.push(Statement::CreateIndex(CreateIndex::from_index(
+                    desired_table,
+                    index,
+                )));
+            }
         }
     }
 
-    for (_name, current_table) in current_tables
-        .iter()
-        .filter(|(name, _)| !desired_tables.contains_key(*name))
-    {
+    for current_table in current.tables.iter().filter(|t| !is_desired(t)) {
         if options.allow_destructive {
             statements.push(Statement::DropTable(DropTable {
                 schema: current_table.schema.clone(),
                 name: current_table.name.clone(),
             }));
         } else {
-            debug_results.push(DebugResults::SkippedDropTable(current_table.name.clone()));
+            let name = display_name(&current_table.schema, &current_table.name);
+            debug_results.push(DebugResults::SkippedDropTable(name));
         }
     }
 
+    let (drop_views, create_views) =
+        diff_views(&current, &desired, &statements, default_schema, options, &mut debug_results);
+    if online {
+        statements = online::online_statements(
+            statements,
+            &current_tables,
+            &desired_tables,
+            &current.views,
+            default_schema,
+            options.backfill_batch_size,
+        );
+    }
+    let (create_sequences, alter_sequences) =
+        diff_sequences(&current, &desired, default_schema, options, &mut debug_results);
+
+    let (create_schemas, drop_schemas) =
+        diff_namespaces(&current, &desired, default_schema, options, &mut debug_results);
+
     // Sort statements topologically based on foreign key dependencies
-    let sorted_statements = topologically_sort_statements(&statements, &desired_tables);
+    let mut sorted_statements = create_schemas;
+    sorted_statements.extend(diff_enums(&current, &desired, default_schema, &mut debug_results));
+    sorted_statements.extend(create_sequences);
+    sorted_statements.extend(drop_views);
+    sorted_statements.extend(plan_table_statements(
+        statements,
+        &current,
+        &desired,
+        default_schema,
+    ));
+    sorted_statements.extend(create_views);
+    sorted_statements.extend(alter_sequences);
+    sorted_statements.extend(drop_schemas);
 
+    let (mut down, irreversible) =
+        reverse::reverse_statements(&sorted_statements, &current, default_schema, dialect);
+    if options.dialect == Some(Dialect::Sqlite) {
+        sorted_statements =
+            sqlite::sqlite_statements(sorted_statements, &current, &desired, default_schema);
+        down = sqlite::sqlite_statements(down, &desired, &current, default_schema);
+    }
+    if dialect == Dialect::Mysql {
+        sorted_statements =
+            mysql::mysql_statements(sorted_statements, &current, &desired, default_schema);
+        down = mysql::mysql_statements(down, &desired, &current, default_schema);
+    }
+    sorted_statements.retain(|s| s.is_supported(dialect));
+    down.retain(|s| s.is_supported(dialect));
     Ok(Migration {
         statements: sorted_statements,
+        down,
+        irreversible,
+        online,
         debug_results,
     })
 }
 
-/// Topologically sorts the migration statements based on foreign key dependencies
-fn topologically_sort_statements(
-    statements: &[Statement],
-    tables: &HashMap<&String, &crate::schema::Table>,
+/// Schemas are created before anything is placed in them, and dropped once their contents are
+/// gone. The default schema is never created or dropped.
+fn diff_namespaces(
+    current: &Schema,
+    desired: &Schema,
+    default_schema: &str,
+    options: &MigrationOptions,
+    debug_results: &mut Vec<DebugResults>,
+) -> (Vec<Statement>, Vec<Statement>) {
+    let current = current.namespaces(default_schema);
+    let desired = desired.namespaces(default_schema);
+    let create = desired
+        .difference(&current)
+        .filter(|s| **s != default_schema)
+        .map(|s| Statement::CreateSchema(CreateSchema::new(s).if_not_exists()))
+        .collect();
+    let mut drop = vec![];
+    for schema in current.difference(&desired).filter(|s| **s != default_schema) {
+        if options.allow_destructive {
+            drop.push(Statement::DropSchema(DropSchema::new(schema)));
+        } else {
+            debug_results.push(DebugResults::SkippedDropSchema(schema.to_string()));
+        }
+    }
+    (create, drop)
+}
+
+/// Enum types are created, or have new variants added, before any table uses them. Postgres can't
+/// remove enum variants, so removed variants are reported in the debug results.
+fn diff_enums(
+    current: &Schema,
+    desired: &Schema,
+    default_schema: &str,
+    debug_results: &mut Vec<DebugResults>,
 ) -> Vec<Statement> {
-    // First, extract create table statements
-    let create_statements: Vec<_> = statements
+    let mut statements = vec![];
+    for desired in &desired.enums {
+        let key = table_key(&desired.schema, &desired.name, default_schema);
+        let current = current
+            .enums
+            .iter()
+            .find(|e| table_key(&e.schema, &e.name, default_schema) == key);
+        let Some(current) = current else {
+            statements.push(Statement::CreateEnum(CreateEnum::from_enum(desired)));
+            continue;
+        };
+        for (i, variant) in desired.variants.iter().enumerate() {
+            if current.variants.contains(variant) {
+                continue;
+            }
+            let position = match i {
+                0 => current.variants.first().cloned().map(EnumValuePosition::Before),
+                _ => Some(EnumValuePosition::After(desired.variants[i - 1].clone())),
+            };
+            statements.push(Statement::AlterEnum(AlterEnum {
+                schema: desired.schema.clone(),
+                name: desired.name.clone(),
+                value: variant.clone(),
+                position,
+            }));
+        }
+        if current.variants.iter().any(|v| !desired.variants.contains(v)) {
+            debug_results.push(DebugResults::SkippedEnumVariantRemoval(desired.name.clone()));
+        }
+    }
+    statements
+}
+
+/// Returns the statements to run before and after the table changes. Postgres won't change the
+/// type of a column a view reads, or drop a view other views read, so those views are dropped
+/// before the change and recreated after it. Materialized views reading backfilled tables, or
+/// recreated views, are refreshed.
+fn diff_views(
+    current: &Schema,
+    desired: &Schema,
+    statements: &[Statement],
+    default_schema: &str,
+    options: &MigrationOptions,
+    debug_results: &mut Vec<DebugResults>,
+) -> (Vec<Statement>, Vec<Statement>) {
+    use crate::query::AlterColumnAction::SetType;
+    let altered_tables = statements
         .iter()
-        .filter(|s| matches!(s, Statement::CreateTable(_)))
-        .collect();
+        .filter_map(|s| match s {
+            Statement::AlterTable(alter) => Some(alter),
+            _ => None,
+        })
+        .filter(|alter| {
+            alter.actions.iter().any(|a| {
+                matches!(
+                    a,
+                    AlterAction::DropColumn { .. }
+                        | AlterAction::AlterColumn {
+                            action: SetType { .. },
+                            ..
+                        }
+                )
+            })
+        })
+        .map(|alter| table_key(&alter.schema, &alter.name, default_schema))
+        .collect::<Vec<_>>();
+    let filled_tables = statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::Backfill(b) => Some(table_key(&b.schema, &b.table, default_schema)),
+            Statement::Update(u) => Some(table_key(&u.schema, &u.table, default_schema)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let same = |a: &View, b: &View| {
+        table_key(&a.schema, &a.name, default_schema)
+            == table_key(&b.schema, &b.name, default_schema)
+    };
+    let reads = |a: &View, b: &View| {
+        a.reads_table(Some(table_key(&b.schema, &b.name, default_schema).0), &b.name)
+    };
+    let find = |views: &'_ [View], view: &View| views.iter().position(|v| same(v, view));
 
-    if create_statements.is_empty() {
-        // If there are no create statements, just return the original
-        return statements.to_vec();
+    // views that have to be dropped, then the views reading them, and so on
+    let mut dropped = current
+        .views
+        .iter()
+        .filter(|current| {
+            let replaced = match find(&desired.views, current) {
+                Some(i) => {
+                    let desired = &desired.views[i];
+                    !desired.lossy_eq(current) && (current.materialized || desired.materialized)
+                }
+                None => options.allow_destructive,
+            };
+            replaced || altered_tables.iter().any(|&(s, t)| current.reads_table(Some(s), t))
+        })
+        .collect::<Vec<_>>();
+    while let Some(view) = current
+        .views
+        .iter()
+        .find(|v| !dropped.contains(v) && dropped.iter().any(|d| reads(v, d)))
+    {
+        dropped.push(view);
     }
+    let is_dropped = |view: &View| dropped.iter().any(|v| same(v, view));
 
-    // Build a map of table name to index in the statements array
-    let mut table_to_index = HashMap::new();
-    for (i, stmt) in create_statements.iter().enumerate() {
-        if let Statement::CreateTable(create) = stmt {
-            table_to_index.insert(create.name.clone(), i);
+    let mut creates = vec![];
+    for desired in &desired.views {
+        let create = CreateView::from_view(desired);
+        match find(&current.views, desired).map(|i| &current.views[i]) {
+            None => creates.push((desired, Statement::CreateView(create))),
+            Some(_) if is_dropped(desired) => {
+                creates.push((desired, Statement::CreateView(create)))
+            }
+            Some(current) if desired.lossy_eq(current) => {}
+            Some(_) => creates.push((desired, Statement::CreateView(create.or_replace()))),
         }
     }
+    for current in &current.views {
+        if find(&desired.views, current).is_some() || options.allow_destructive {
+            continue;
+        }
+        if is_dropped(current) {
+            creates.push((current, Statement::CreateView(CreateView::from_view(current))));
+        } else {
+            let name = display_name(&current.schema, &current.name);
+            debug_results.push(DebugResults::SkippedDropView(name));
+        }
+    }
+    // the data of the views created here is up to date
+    let mut refreshed = vec![];
+    while let Some(view) = desired.views.iter().find(|v| {
+        v.materialized
+            && !refreshed.contains(v)
+            && !creates.iter().any(|(c, _)| same(c, v))
+            && (filled_tables.iter().any(|&(s, t)| v.reads_table(Some(s), t))
+                || creates
+                    .iter()
+                    .map(|(c, _)| *c)
+                    .chain(refreshed.iter().copied())
+                    .any(|c| reads(v, c)))
+    }) {
+        refreshed.push(view);
+    }
+    creates.extend(refreshed.into_iter().map(|v| {
+        let refresh = RefreshMaterializedView::new(&v.name).schema(v.schema.clone());
+        (v, Statement::RefreshMaterializedView(refresh))
+    }));
 
-    // Set up topological sort
-    let mut topo_sort = TopoSort::new();
-
-    // Find table dependencies and add them to topo_sort
-    for stmt in &create_statements {
-        if let Statement::CreateTable(create) = stmt {
-            let table_name = &create.name;
-            let mut dependencies = Vec::new();
+    let drops = dropped
+        .into_iter()
+        .map(|v| (v, Statement::DropView(DropView::from_view(v))))
+        .collect();
+    (
+        order_views(drops, default_schema, true),
+        order_views(creates, default_schema, false),
+    )
+}
 
-            // Get the actual table from the tables map
-            if let Some(table) = tables.values().find(|t| &t.name == table_name) {
-                // Check all columns for foreign key constraints
-                for column in &table.columns {
-                    if let Some(Constraint::ForeignKey(fk)) = &column.constraint {
-                        dependencies.push(fk.table.clone());
+/// Order the statements so views come after the views they read, or before them if
+/// `dependents_first`.
+fn order_views(
+    mut remaining: Vec<(&View, Statement)>,
+    default_schema: &str,
+    dependents_first: bool,
+) -> Vec<Statement> {
+    let schema = |v: &View| v.schema.clone().unwrap_or_else(|| default_schema.to_string());
+    let reads = |a: &View, b: &View| a.reads_table(Some(&schema(b)), &b.name);
+    let mut ordered = vec![];
+    while !remaining.is_empty() {
+        let ready = |(view, _): &(&View, Statement)| {
+            !remaining.iter().any(|(other, _)| {
+                (schema(other), &other.name) != (schema(view), &view.name)
+                    && match dependents_first {
+                        true => reads(other, view),
+                        false => reads(view, other),
                     }
+            })
+        };
+        // views can't read each other, but fall back to the given order if they seem to
+        let i = remaining.iter().position(ready).unwrap_or(0);
+        ordered.push(remaining.remove(i).1);
+    }
+    ordered
+}
+
+/// Returns the statements to run before and after the table changes. Sequences are created
+/// first, so column defaults can use them, but `OWNED BY` and drops wait until the tables are in
+/// place. Sequences owned by a column are dropped with it, so they're never dropped here.
+fn diff_sequences(
+    current: &Schema,
+    desired: &Schema,
+    default_schema: &str,
+    options: &MigrationOptions,
+    debug_results: &mut Vec<DebugResults>,
+) -> (Vec<Statement>, Vec<Statement>) {
+    let same = |a: &Sequence, b: &Sequence| {
+        table_key(&a.schema, &a.name, default_schema)
+            == table_key(&b.schema, &b.name, default_schema)
+    };
+    let mut before = vec![];
+    let mut after = vec![];
+    for desired in &desired.sequences {
+        let changes = match current.sequences.iter().find(|s| same(s, desired)) {
+            Some(current) => current.options.changes(&desired.options),
+            None => {
+                let mut create = CreateSequence::from_sequence(desired);
+                let owned_by = create.options.owned_by.take();
+                before.push(Statement::CreateSequence(create));
+                SequenceOptions {
+                    owned_by,
+                    ..SequenceOptions::default()
                 }
             }
+        };
+        if !changes.is_empty() {
+            after.push(Statement::AlterSequence(AlterSequence {
+                schema: desired.schema.clone(),
+                name: desired.name.clone(),
+                options: changes,
+            }));
+        }
+    }
+    for current in &current.sequences {
+        if desired.sequences.iter().any(|s| same(s, current))
+            || current.options.owned_by.is_some()
+        {
+            continue;
+        }
+        if options.allow_destructive {
+            after.push(Statement::DropSequence(DropSequence {
+                schema: current.schema.clone(),
+                name: current.name.clone(),
+            }));
+        } else {
+            let name = display_name(&current.schema, &current.name);
+            debug_results.push(DebugResults::SkippedDropSequence(name));
+        }
+    }
+    (before, after)
+}
 
-            // Add this table and its dependencies to the topo_sort
-            dbg!(table_name, &dependencies);
-            topo_sort.insert(table_name.clone(), dependencies);
+/// Column-level constraints as table-level ones, named by `Constraint::default_name`.
+fn column_constraints(table: &Table) -> Vec<TableConstraint> {
+    table
+        .columns
+        .iter()
+        .filter_map(|c| {
+            let constraint = c.constraint.clone()?;
+            Some(TableConstraint {
+                name: constraint.default_name(&table.name, &c.name),
+                columns: vec![c.name.clone()],
+                constraint,
+            })
+        })
+        .collect()
+}
+
+/// Constraints are matched by name, or by definition, so a constraint declared on a column
+/// matches the same constraint read back from the database under its real name.
+fn diff_constraints(current: &Table, desired: &Table, actions: &mut Vec<AlterAction>) {
+    let same = |a: &TableConstraint, b: &TableConstraint| a.lossy_eq(b);
+    // a foreign key on the same columns, e.g. pointing at a different table, is replaced.
+    let replaces = |current: &TableConstraint, desired: &TableConstraint| {
+        matches!(
+            (&current.constraint, &desired.constraint),
+            (Constraint::ForeignKey(_), Constraint::ForeignKey(_))
+        ) && current.columns == desired.columns
+    };
+    let current_column_constraints = column_constraints(current);
+    let current_all = current_column_constraints
+        .iter()
+        .chain(current.constraints.iter())
+        .collect::<Vec<_>>();
+    // new columns are added along with their constraints
+    let desired_column_constraints = column_constraints(desired)
+        .into_iter()
+        .filter(|c| current.columns.iter().any(|col| col.name == c.columns[0]))
+        .collect::<Vec<_>>();
+    let desired_all = desired_column_constraints
+        .iter()
+        .chain(desired.constraints.iter())
+        .collect::<Vec<_>>();
+
+    let mut dropped = vec![];
+    for desired in &desired_all {
+        if current_all.iter().any(|c| same(c, desired)) {
+            continue;
+        }
+        let replaced = current_all
+            .iter()
+            .find(|c| c.name == desired.name)
+            .or_else(|| {
+                current_all.iter().find(|c| {
+                    replaces(c, desired) && !desired_all.iter().any(|d| same(c, d))
+                })
+            });
+        let mut constraint = (*desired).clone();
+        if let Some(replaced) = replaced {
+            actions.push(AlterAction::drop_constraint(replaced.name.clone()));
+            dropped.push(replaced.name.clone());
+            // column-level constraints don't have a name of their own, so keep the existing one
+            if desired_column_constraints.iter().any(|c| c == *desired) {
+                constraint.name = replaced.name.clone();
+            }
+        }
+        actions.push(AlterAction::add_table_constraint(constraint));
+    }
+    for current in current_all {
+        // constraints on columns missing from `desired` are left alone, like the columns themselves
+        let columns_exist = current
+            .columns
+            .iter()
+            .all(|name| desired.columns.iter().any(|c| &c.name == name));
+        if !columns_exist || dropped.contains(&current.name) {
+            continue;
+        }
+        if !desired_all
+            .iter()
+            .any(|d| d.name == current.name || same(current, d))
+        {
+            actions.push(AlterAction::drop_constraint(current.name.clone()));
         }
     }
+}
+
+enum GeneratedDiff {
+    Done,
+    /// The identity sequence starts at 1, so it has to skip past the existing values. Contains
+    /// the sequence of the serial column it replaces, by schema and name.
+    AddedIdentity(Option<(Option<String>, String)>),
+    /// The column can't be altered into its desired form, and has to be dropped and re-added.
+    Rebuild,
+}
 
-    // Perform the sort
-    let table_order = match topo_sort.into_vec_nodes() {
-        SortResults::Full(nodes) => nodes,
-        SortResults::Partial(nodes) => {
-            // Return partial results even if there's a cycle
-            nodes
+fn diff_generated(current: &Column, desired: &Column, actions: &mut Vec<AlterAction>) -> GeneratedDiff {
+    use crate::query::AlterColumnAction::*;
+    use Generated::*;
+    let action = match (&current.generated, &desired.generated) {
+        (None, None) => return GeneratedDiff::Done,
+        (Some(a), Some(b)) if a.lossy_eq(b) => return GeneratedDiff::Done,
+        (Some(Identity { .. }), Some(Identity { always })) => SetIdentity { always: *always },
+        (None, Some(Identity { always })) => {
+            // a serial column, i.e. one using a sequence as its default
+            let serial = current.default.as_ref().and_then(default_sequence);
+            if serial.is_none() && current.default.as_ref().is_some_and(is_sequence_default) {
+                actions.push(AlterAction::drop_default(desired.name.clone()));
+            }
+            actions.push(AlterAction::AlterColumn {
+                name: desired.name.clone(),
+                action: AddIdentity { always: *always },
+            });
+            return GeneratedDiff::AddedIdentity(serial);
         }
+        (Some(Identity { .. }), None) => DropIdentity,
+        (Some(Stored(_)), Some(Stored(expr))) => SetExpression(expr.clone()),
+        (Some(Stored(_)), None) => DropExpression,
+        _ => return GeneratedDiff::Rebuild,
     };
+    actions.push(AlterAction::AlterColumn {
+        name: desired.name.clone(),
+        action,
+    });
+    GeneratedDiff::Done
+}
 
-    // First create a sorted list of CREATE TABLE statements
-    let mut sorted_statements = Vec::new();
-    for table_name in &table_order {
-        if let Some(&idx) = table_to_index.get(table_name) {
-            sorted_statements.push(create_statements[idx].clone());
+/// `SELECT setval(...)` to move the identity sequence of `column` past the existing rows.
+fn reseed_identity(table: &Table, column: &str) -> Select {
+    let mut table_name = String::new();
+    table_name.push_table_name(&table.schema, &table.name);
+    let mut setval = String::from("setval(pg_get_serial_sequence(");
+    setval.push_literal(&table_name, Dialect::Postgres);
+    setval.push_str(", ");
+    setval.push_literal(column, Dialect::Postgres);
+    setval.push_str("), coalesce(max(");
+    setval.push_quoted(column);
+    setval.push_str("), 0) + 1, false)");
+    let mut select = Select::default().select_raw(setval).from(&table.name);
+    if let Some(from) = &mut select.from {
+        from.schema = table.schema.clone();
+    }
+    select
+}
+
+/// Backfill `column`, in batches if `batch_size` is set and the table has an integer primary key.
+fn backfill_column(table: &Table, column: &str, value: Expr, batch_size: Option<u64>) -> Backfill {
+    let backfill = Backfill::new(&table.name, column, value).schema(table.schema.clone());
+    let key = match table.primary_key_columns().as_slice() {
+        [key] => table.columns.iter().find(|c| &c.name == key),
+        _ => None,
+    };
+    match (key, batch_size) {
+        (Some(key), Some(batch_size))
+            if matches!(
+                key.typ,
+                Type::I16 | Type::I32 | Type::I64 | Type::Serial | Type::BigSerial
+            ) =>
+        {
+            backfill.batched(&key.name, batch_size)
         }
+        _ => backfill,
     }
+}
+
+fn is_sequence_default(expr: &Expr) -> bool {
+    matches!(expr, Expr::Raw(s) if s.trim_start().to_lowercase().starts_with("nextval("))
+}
 
-    // Add remaining statements (non-create-table) in their original order
-    for stmt in statements {
-        if !matches!(stmt, Statement::CreateTable(_)) {
-            sorted_statements.push(stmt.clone());
+/// The schema and name of the sequence in a `nextval('...'::regclass)` default.
+fn default_sequence(expr: &Expr) -> Option<(Option<String>, String)> {
+    let Expr::Raw(s) = expr else {
+        return None;
+    };
+    if !is_sequence_default(expr) {
+        return None;
+    }
+    let start = s.find('\'')? + 1;
+    let end = start + s[start..].find('\'')?;
+    let mut parts = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = s[start..end].chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                parts.last_mut()?.push('"');
+            }
+            '"' => quoted = !quoted,
+            '.' if !quoted => parts.push(String::new()),
+            c => parts.last_mut()?.push(c),
         }
     }
+    let name = parts.pop()?;
+    Some((parts.pop(), name))
+}
 
-    sorted_statements
+/// The foreign keys declared on columns and on the table.
+fn foreign_keys<'a>(
+    columns: &'a [Column],
+    constraints: &'a [TableConstraint],
+) -> impl Iterator<Item = &'a ForeignKey> {
+    let column_fks = columns.iter().filter_map(|c| match &c.constraint {
+        Some(Constraint::ForeignKey(fk)) => Some(fk),
+        _ => None,
+    });
+    let table_fks = constraints.iter().filter_map(|c| match &c.constraint {
+        Constraint::ForeignKey(fk) => Some(fk),
+        _ => None,
+    });
+    column_fks.chain(table_fks)
+}
+
+/// The table a foreign key references. Without a schema, that's the schema of the referencing
+/// table.
+fn referenced<'a>(fk: &'a ForeignKey, schema: &'a str) -> TableKey<'a> {
+    (fk.schema.as_deref().unwrap_or(schema), &fk.table)
+}
+
+/// Orders the table statements so foreign keys always reference existing tables. New tables are
+/// created before the tables referencing them, and dropped tables are dropped after them. Other
+/// statements keep their order, between the creates and the drops.
+fn plan_table_statements(
+    statements: Vec<Statement>,
+    current: &Schema,
+    desired: &Schema,
+    default_schema: &str,
+) -> Vec<Statement> {
+    let position = |tables: &[Table], schema: &Option<String>, name: &str| {
+        let key = table_key(schema, name, default_schema);
+        tables
+            .iter()
+            .position(|t| table_key(&t.schema, &t.name, default_schema) == key)
+    };
+    let mut creates = vec![];
+    let mut drops = vec![];
+    let mut rest = vec![];
+    for statement in statements {
+        match statement {
+            Statement::CreateTable(c) => creates.push(c),
+            Statement::DropTable(d) => drops.push(d),
+            statement => rest.push(statement),
+        }
+    }
+    creates.sort_by_key(|c| position(&desired.tables, &c.schema, &c.name));
+    drops.sort_by_key(|d| position(&current.tables, &d.schema, &d.name));
+
+    let (creates, deferred) = order_creates(creates, default_schema);
+    let mut planned = creates
+        .into_iter()
+        .map(Statement::CreateTable)
+        .collect::<Vec<_>>();
+    planned.extend(rest);
+    planned.extend(deferred);
+    planned.extend(order_drops(drops, current, default_schema));
+    planned
+}
+
+/// Creates each table after the tables it references. Tables referencing each other in a cycle
+/// are created without those foreign keys, which are returned to add once all of them exist.
+fn order_creates(
+    mut creates: Vec<CreateTable>,
+    default_schema: &str,
+) -> (Vec<CreateTable>, Vec<Statement>) {
+    let mut ordered = vec![];
+    let mut deferred = vec![];
+    while !creates.is_empty() {
+        let pending = creates
+            .iter()
+            .map(|c| table_key(&c.schema, &c.name, default_schema))
+            .map(|(schema, name)| (schema.to_string(), name.to_string()))
+            .collect::<Vec<_>>();
+        let waits = |c: &CreateTable| {
+            let key = table_key(&c.schema, &c.name, default_schema);
+            foreign_keys(&c.columns, &c.constraints)
+                .map(|fk| referenced(fk, key.0))
+                .any(|r| r != key && pending.iter().any(|p| (p.0.as_str(), p.1.as_str()) == r))
+        };
+        let ready = creates.iter().position(|c| !waits(c));
+        let i = match ready {
+            Some(i) => i,
+            // every remaining table waits on another one
+            None => {
+                let (create, alter) = defer_foreign_keys(&creates[0], &pending, default_schema);
+                creates[0] = create;
+                deferred.push(alter);
+                0
+            }
+        };
+        ordered.push(creates.remove(i));
+    }
+    (ordered, deferred)
+}
+
+/// Moves the foreign keys of `create` that reference `pending` tables into an `ALTER TABLE`.
+fn defer_foreign_keys(
+    create: &CreateTable,
+    pending: &[(String, String)],
+    default_schema: &str,
+) -> (CreateTable, Statement) {
+    let mut create = create.clone();
+    let key = table_key(&create.schema, &create.name, default_schema);
+    let (schema, name) = (key.0.to_string(), key.1.to_string());
+    let defer = |constraint: &Constraint| match constraint {
+        Constraint::ForeignKey(fk) => {
+            let r = referenced(fk, &schema);
+            r != (schema.as_str(), name.as_str())
+                && pending.iter().any(|p| (p.0.as_str(), p.1.as_str()) == r)
+        }
+        _ => false,
+    };
+    let mut actions = vec![];
+    for column in &mut create.columns {
+        if column.constraint.as_ref().is_some_and(defer) {
+            let constraint = column.constraint.take().expect("checked above");
+            actions.push(AlterAction::add_constraint(&name, column.name.clone(), constraint));
+        }
+    }
+    let (deferred, kept) = create
+        .constraints
+        .drain(..)
+        .partition::<Vec<_>, _>(|c| defer(&c.constraint));
+    create.constraints = kept;
+    actions.extend(deferred.into_iter().map(AlterAction::add_table_constraint));
+    let alter = Statement::AlterTable(AlterTable {
+        schema: create.schema.clone(),
+        name: create.name.clone(),
+        actions,
+        enums: vec![],
+    });
+    (create, alter)
+}
+
+/// Drops each table after the tables referencing it. Foreign keys between tables in a cycle are
+/// dropped first.
+fn order_drops(
+    mut drops: Vec<DropTable>,
+    current: &Schema,
+    default_schema: &str,
+) -> Vec<Statement> {
+    // the foreign keys of each dropped table, by name and referenced table
+    let mut references = drops
+        .iter()
+        .map(|d| {
+            let key = table_key(&d.schema, &d.name, default_schema);
+            let Some(table) = current
+                .tables
+                .iter()
+                .find(|t| table_key(&t.schema, &t.name, default_schema) == key)
+            else {
+                return vec![];
+            };
+            column_constraints(table)
+                .into_iter()
+                .chain(table.constraints.iter().cloned())
+                .filter_map(|c| match &c.constraint {
+                    Constraint::ForeignKey(fk) => {
+                        let (schema, name) = referenced(fk, key.0);
+                        Some((c.name.clone(), (schema.to_string(), name.to_string())))
+                    }
+                    _ => None,
+                })
+                .filter(|(_, r)| (r.0.as_str(), r.1.as_str()) != key)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut ordered = vec![];
+    while !drops.is_empty() {
+        let referenced_by = |d: &DropTable, references: &[Vec<(String, (String, String))>]| {
+            let (schema, name) = table_key(&d.schema, &d.name, default_schema);
+            references
+                .iter()
+                .enumerate()
+                .filter(|(_, fks)| fks.iter().any(|(_, r)| r.0 == schema && r.1 == name))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>()
+        };
+        let ready = drops
+            .iter()
+            .position(|d| referenced_by(d, &references).is_empty());
+        let i = match ready {
+            Some(i) => i,
+            // every remaining table is referenced by another one
+            None => {
+                let (schema, name) = table_key(&drops[0].schema, &drops[0].name, default_schema);
+                let (schema, name) = (schema.to_string(), name.to_string());
+                for j in referenced_by(&drops[0], &references) {
+                    let (dropped, kept) = references[j]
+                        .drain(..)
+                        .partition::<Vec<_>, _>(|(_, r)| r.0 == schema && r.1 == name);
+                    references[j] = kept;
+                    ordered.push(Statement::AlterTable(AlterTable {
+                        schema: drops[j].schema.clone(),
+                        name: drops[j].name.clone(),
+                        actions: dropped
+                            .into_iter()
+                            .map(|(name, _)| AlterAction::drop_constraint(name))
+                            .collect(),
+                        enums: vec![],
+                    }));
+                }
+                0
+            }
+        };
+        references.remove(i);
+        ordered.push(Statement::DropTable(drops.remove(i)));
+    }
+    ordered
 }
 
 #[derive(Debug)]
 pub struct Migration {
     pub statements: Vec<Statement>,
+    /// Statements undoing `statements`, restoring the schema the migration was computed from.
+    pub down: Vec<Statement>,
+    /// Steps that `down` can't fully undo, e.g. because they drop data.
+    pub irreversible: Vec<Irreversible>,
+    /// Planned with `MigrationOptions::online`, so each step that scans or rewrites a table
+    /// should commit before the next.
+    pub online: bool,
     pub debug_results: Vec<DebugResults>,
 }
 
@@ -231,25 +1192,118 @@ impl Migration {
         self.statements.is_empty()
     }
 
+    /// Whether `down` fully undoes the migration.
+    pub fn is_reversible(&self) -> bool {
+        self.irreversible.is_empty()
+    }
+
     pub fn set_schema(&mut self, schema_name: &str) {
-        for statement in &mut self.statements {
+        for statement in self.statements.iter_mut().chain(self.down.iter_mut()) {
             statement.set_schema(schema_name);
         }
     }
+
+    /// The statements, with their indexes, grouped into the transactions they run in, and
+    /// whether each group is a transaction. Consecutive statements share a transaction, except
+    /// those that can't run in one, like `CREATE INDEX CONCURRENTLY`, which run on their own.
+    /// For an online migration, each statement that scans or rewrites a table starts a new
+    /// transaction, so the locks taken before it are released, unless it follows a
+    /// `LOCK TABLE`.
+    pub fn transactions(&self) -> Vec<(bool, Vec<(usize, &Statement)>)> {
+        transactions(&self.statements, self.online)
+    }
+}
+
+fn transactions(statements: &[Statement], online: bool) -> Vec<(bool, Vec<(usize, &Statement)>)> {
+    let mut transactions: Vec<(bool, Vec<(usize, &Statement)>)> = vec![];
+    for (i, statement) in statements.iter().enumerate() {
+        let transactional = statement.is_transactional();
+        let long_running = online && {
+            let risk = statement.risk();
+            risk.scans_table || risk.rewrites_table
+        };
+        // an explicit lock is taken in a new transaction, which the statement after it joins
+        let locks = online && matches!(statement, Statement::LockTable(_));
+        let after_lock = |batch: &[(usize, &Statement)]| {
+            matches!(batch, [(_, Statement::LockTable(_))])
+        };
+        match transactions.last_mut() {
+            Some((true, batch))
+                if transactional && !locks && (!long_running || after_lock(batch)) =>
+            {
+                batch.push((i, statement))
+            }
+            _ => transactions.push((transactional, vec![(i, statement)])),
+        }
+    }
+    transactions
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Statement {
+    CreateSchema(CreateSchema),
+    DropSchema(DropSchema),
+    CreateEnum(CreateEnum),
+    AlterEnum(AlterEnum),
+    DropEnum(DropEnum),
     CreateTable(CreateTable),
     CreateIndex(CreateIndex),
+    DropIndex(DropIndex),
     AlterTable(AlterTable),
     DropTable(DropTable),
     Update(Update),
+    Backfill(Backfill),
+    /// Held until the end of the transaction, e.g. so no rows change while columns are swapped.
+    LockTable(LockTable),
+    /// Keeps a column in sync with the others while it's backfilled.
+    CreateTrigger(CreateTrigger),
+    DropTrigger(DropTrigger),
+    /// A query run for its side effects, e.g. to reset a sequence.
+    Select(Select),
+    CommentOn(CommentOn),
+    CreateView(CreateView),
+    DropView(DropView),
+    RefreshMaterializedView(RefreshMaterializedView),
+    CreateSequence(CreateSequence),
+    AlterSequence(AlterSequence),
+    DropSequence(DropSequence),
+    /// Tables are rebuilt for changes SQLite can't make in place.
+    RebuildTable(RebuildTable),
+    Pragma(Pragma),
 }
 
 impl Statement {
+    /// Whether the statement does anything in `dialect`. Only Postgres has enum types, which
+    /// other dialects declare inline, and sequences, and SQLite has no comments.
+    pub fn is_supported(&self, dialect: Dialect) -> bool {
+        match self {
+            Statement::CreateEnum(_)
+            | Statement::AlterEnum(_)
+            | Statement::DropEnum(_)
+            | Statement::CreateSequence(_)
+            | Statement::AlterSequence(_)
+            | Statement::DropSequence(_)
+            | Statement::CreateTrigger(_)
+            | Statement::DropTrigger(_) => dialect == Dialect::Postgres,
+            Statement::CommentOn(_) => dialect != Dialect::Sqlite,
+            _ => true,
+        }
+    }
+
     pub fn set_schema(&mut self, schema_name: &str) {
         match self {
+            // these statements name the schema itself
+            Statement::CreateSchema(_) | Statement::DropSchema(_) => {}
+            Statement::CreateEnum(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::AlterEnum(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::DropEnum(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
             Statement::CreateTable(s) => {
                 s.schema = Some(schema_name.to_string());
             }
@@ -262,19 +1316,102 @@ impl Statement {
             Statement::CreateIndex(s) => {
                 s.schema = Some(schema_name.to_string());
             }
+            Statement::DropIndex(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
             Statement::Update(s) => {
                 s.schema = Some(schema_name.to_string());
             }
+            Statement::Backfill(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::LockTable(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::CreateTrigger(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::DropTrigger(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::Select(s) => {
+                if let Some(from) = &mut s.from {
+                    from.schema = Some(schema_name.to_string());
+                }
+            }
+            Statement::CommentOn(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::CreateView(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::DropView(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::RefreshMaterializedView(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::CreateSequence(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::AlterSequence(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::DropSequence(s) => {
+                s.schema = Some(schema_name.to_string());
+            }
+            Statement::RebuildTable(s) => {
+                s.table.schema = Some(schema_name.to_string());
+                for index in &mut s.indexes {
+                    index.schema = Some(schema_name.to_string());
+                }
+            }
+            Statement::Pragma(_) => {}
+        }
+    }
+
+    /// Whether the statement can run inside a transaction. `CREATE INDEX CONCURRENTLY` can't, a
+    /// value added to an enum can't be used in the transaction that adds it, and a batched
+    /// backfill commits each batch. Setting SQLite's `foreign_keys` pragma has no effect in one.
+    pub fn is_transactional(&self) -> bool {
+        match self {
+            Statement::Pragma(p) => p.is_transactional(),
+            Statement::CreateIndex(s) => !s.concurrently,
+            Statement::Backfill(s) => !s.is_batched(),
+            Statement::AlterEnum(_) => false,
+            _ => true,
         }
     }
 
+    /// The table the statement applies to. For enum, view, sequence and pragma statements, their
+    /// name.
     pub fn table_name(&self) -> &str {
         match self {
+            Statement::CreateSchema(s) => &s.name,
+            Statement::DropSchema(s) => &s.name,
+            Statement::CreateEnum(s) => &s.name,
+            Statement::AlterEnum(s) => &s.name,
+            Statement::DropEnum(s) => &s.name,
             Statement::CreateTable(s) => &s.name,
             Statement::AlterTable(s) => &s.name,
             Statement::DropTable(s) => &s.name,
             Statement::CreateIndex(s) => &s.table,
+            Statement::DropIndex(s) => &s.table,
             Statement::Update(s) => &s.table,
+            Statement::Backfill(s) => &s.table,
+            Statement::LockTable(s) => &s.name,
+            Statement::CreateTrigger(s) => &s.table,
+            Statement::DropTrigger(s) => &s.table,
+            Statement::Select(s) => s.from.as_ref().map_or("", |f| &f.table),
+            Statement::CommentOn(s) => &s.table,
+            Statement::CreateView(s) => &s.name,
+            Statement::DropView(s) => &s.name,
+            Statement::RefreshMaterializedView(s) => &s.name,
+            Statement::CreateSequence(s) => &s.name,
+            Statement::AlterSequence(s) => &s.name,
+            Statement::DropSequence(s) => &s.name,
+            Statement::RebuildTable(s) => &s.table.name,
+            Statement::Pragma(s) => &s.name,
         }
     }
 }
@@ -283,11 +1420,31 @@ impl ToSql for Statement {
     fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         use Statement::*;
         match self {
+            CreateSchema(c) => c.write_sql(buf, dialect),
+            DropSchema(d) => d.write_sql(buf, dialect),
+            CreateEnum(c) => c.write_sql(buf, dialect),
+            AlterEnum(a) => a.write_sql(buf, dialect),
+            DropEnum(d) => d.write_sql(buf, dialect),
             CreateTable(c) => c.write_sql(buf, dialect),
             CreateIndex(c) => c.write_sql(buf, dialect),
+            DropIndex(d) => d.write_sql(buf, dialect),
             AlterTable(a) => a.write_sql(buf, dialect),
             DropTable(d) => d.write_sql(buf, dialect),
             Update(u) => u.write_sql(buf, dialect),
+            Backfill(b) => b.write_sql(buf, dialect),
+            LockTable(l) => l.write_sql(buf, dialect),
+            CreateTrigger(c) => c.write_sql(buf, dialect),
+            DropTrigger(d) => d.write_sql(buf, dialect),
+            Select(s) => s.write_sql(buf, dialect),
+            CommentOn(c) => c.write_sql(buf, dialect),
+            CreateView(c) => c.write_sql(buf, dialect),
+            DropView(d) => d.write_sql(buf, dialect),
+            RefreshMaterializedView(r) => r.write_sql(buf, dialect),
+            CreateSequence(c) => c.write_sql(buf, dialect),
+            AlterSequence(a) => a.write_sql(buf, dialect),
+            DropSequence(d) => d.write_sql(buf, dialect),
+            RebuildTable(r) => r.write_sql(buf, dialect),
+            Pragma(p) => p.write_sql(buf, dialect),
         }
     }
 }
@@ -295,7 +1452,32 @@ impl ToSql for Statement {
 #[derive(Debug)]
 pub enum DebugResults {
     TablesIdentical(String),
+    /// Contains the table name, qualified with its schema if it has one.
     SkippedDropTable(String),
+    /// Postgres can't remove variants from an enum. Contains the enum name.
+    SkippedEnumVariantRemoval(String),
+    /// A column type change that can fail or lose data. Impossible conversions are skipped
+    /// unless `allow_destructive` is set and `MigrationOptions::using` gives the conversion.
+    RiskyTypeConversion {
+        table: String,
+        column: String,
+        from: Type,
+        to: Type,
+        conversion: Conversion,
+        skipped: bool,
+    },
+    /// A column can only be made generated, or change between identity and generated, by
+    /// dropping and re-adding it. Skipped unless `allow_destructive` is set.
+    SkippedColumnRebuild { table: String, column: String },
+    /// A `NOT NULL` column without a default, whose existing rows need a value. Skipped until
+    /// one is given with `MigrationOptions::backfill`.
+    SkippedAddColumn { table: String, column: String },
+    /// Contains the view name, qualified like `SkippedDropTable`.
+    SkippedDropView(String),
+    /// Contains the sequence name, qualified like `SkippedDropTable`.
+    SkippedDropSequence(String),
+    /// A schema that's no longer used. Skipped unless `allow_destructive` is set.
+    SkippedDropSchema(String),
 }
 
 impl DebugResults {
@@ -303,6 +1485,13 @@ impl DebugResults {
         match self {
             DebugResults::TablesIdentical(name) => name,
             DebugResults::SkippedDropTable(name) => name,
+            DebugResults::SkippedEnumVariantRemoval(name) => name,
+            DebugResults::RiskyTypeConversion { table, .. } => table,
+            DebugResults::SkippedColumnRebuild { table, .. } => table,
+            DebugResults::SkippedAddColumn { table, .. } => table,
+            DebugResults::SkippedDropView(name) => name,
+            DebugResults::SkippedDropSequence(name) => name,
+            DebugResults::SkippedDropSchema(name) => name,
         }
     }
 }
@@ -311,11 +1500,13 @@ impl DebugResults {
 mod tests {
     use super::*;
 
-    use crate::schema::{Column, Constraint, ForeignKey};
-    use crate::Table;
+    use crate::schema::{
+        Column, Constraint, Enum, ForeignKey, ReferentialAction, Sequence, View,
+    };
     use crate::Type;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_drop_table() {
         let empty_schema = Schema::default();
         let mut single_table_schema = Schema::default();
@@ -352,41 +1543,843 @@ mod tests {
         assert!(migrations.statements.is_empty());
     }
 
+    #[test]
+    fn test_column_default() {
+        let column = |default: Option<&str>| Column {
+            default: default.map(Expr::from),
+            ..Column::new("created_at", Type::DateTime).not_null()
+        };
+        let schema = |default: Option<&str>| Schema {
+            tables: vec![Table::new("user").column(column(default))],
+            ..Schema::default()
+        };
+        let options = MigrationOptions::default();
+
+        let migration = migrate(
+            schema(Some("now()")),
+            schema(Some("clock_timestamp()")),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" ALTER COLUMN "created_at" SET DEFAULT clock_timestamp()"#
+        );
+
+        let migration = migrate(schema(Some("now()")), schema(None), &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" ALTER COLUMN "created_at" DROP DEFAULT"#
+        );
+
+        let migration =
+            migrate(schema(Some("'foo'::text")), schema(Some("'foo'")), &options).unwrap();
+        assert!(migration.is_empty());
+
+        let current = schema(Some("nextval('user_id_seq'::regclass)"));
+        let migration = migrate(current, schema(None), &options).unwrap();
+        assert!(migration.is_empty());
+    }
+
+    #[test]
+    fn test_backfill_not_null_column() {
+        let column = |name: &str, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, Type::I32)
+        };
+        let id = Column::new("id", Type::I32).primary_key();
+        let current = Schema {
+            tables: vec![Table::new("user").column(id.clone())],
+            ..Schema::default()
+        };
+        let desired = |column: Column| Schema {
+            tables: vec![Table::new("user").column(id.clone()).column(column)],
+            ..Schema::default()
+        };
+        let sql = |migration: Migration| {
+            migration
+                .statements
+                .iter()
+                .map(|s| s.to_sql(Dialect::Postgres))
+                .collect::<Vec<_>>()
+        };
+
+        // the default fills the existing rows
+        let with_default = column("score", false).default("0");
+        let options = MigrationOptions::default();
+        let migration = migrate(current.clone(), desired(with_default), &options);
+        assert_eq!(
+            sql(migration.unwrap()),
+            vec![r#"ALTER TABLE "user" ADD COLUMN "score" integer NOT NULL DEFAULT 0"#]
+        );
+
+        let migration = migrate(
+            current.clone(),
+            desired(column("score", false)),
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        // the existing rows need a value
+        assert!(migration.statements.is_empty());
+        assert!(matches!(
+            &migration.debug_results[0],
+            DebugResults::SkippedAddColumn { table, column } if table == "user" && column == "score"
+        ));
+
+        let options = MigrationOptions {
+            backfill_batch_size: Some(1000),
+            ..MigrationOptions::default()
+        }
+        .backfill("user", "score", Expr::Raw("\"id\" * 10".to_string()));
+        let migration = migrate(current, desired(column("score", false)), &options).unwrap();
+        let Statement::Backfill(backfill) = &migration.statements[1] else {
+            panic!("expected a backfill, got {:?}", migration.statements[1]);
+        };
+        assert_eq!(backfill.key.as_deref(), Some("id"));
+        assert!(!migration.statements[1].is_transactional());
+        assert_eq!(
+            sql(migration),
+            vec![
+                r#"ALTER TABLE "user" ADD COLUMN "score" integer"#,
+                r#"UPDATE "user" SET "score" = "id" * 10 WHERE "score" IS NULL"#,
+                r#"ALTER TABLE "user" ALTER COLUMN "score" SET NOT NULL"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_length_change() {
+        let schema = |typ: Type| Schema {
+            tables: vec![Table::new("user").column(Column::new("email", typ).not_null())],
+            ..Schema::default()
+        };
+        let options = MigrationOptions::default();
+
+        let migration = migrate(
+            schema(Type::Varchar(64)),
+            schema(Type::Varchar(255)),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" ALTER COLUMN "email" TYPE character varying(255) USING "email"::character varying(255)"#
+        );
+
+        let migration =
+            migrate(schema(Type::Timestamp(6)), schema(Type::NaiveDateTime), &options).unwrap();
+        assert!(migration.is_empty());
+    }
+
+    #[test]
+    fn test_type_conversion() {
+        let schema = |typ: Type| Schema {
+            tables: vec![Table::new("user").column(Column::new("active", typ))],
+            ..Schema::default()
+        };
+        let options = MigrationOptions::default();
+
+        let migration = migrate(schema(Type::Boolean), schema(Type::I16), &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" ALTER COLUMN "active" TYPE smallint USING CASE WHEN "active" THEN 1 ELSE 0 END"#
+        );
+
+        let migration = migrate(schema(Type::Text), schema(Type::I32), &options).unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        assert!(matches!(
+            migration.debug_results[0],
+            DebugResults::RiskyTypeConversion {
+                conversion: Conversion::Lossy,
+                skipped: false,
+                ..
+            }
+        ));
+
+        let migration = migrate(schema(Type::Boolean), schema(Type::Date), &options).unwrap();
+        assert!(migration.is_empty());
+        assert!(matches!(
+            migration.debug_results[0],
+            DebugResults::RiskyTypeConversion { skipped: true, .. }
+        ));
+
+        let options = MigrationOptions {
+            allow_destructive: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(schema(Type::Boolean), schema(Type::Date), &options).unwrap();
+        assert!(migration.is_empty());
+        assert!(matches!(
+            migration.debug_results[0],
+            DebugResults::RiskyTypeConversion { skipped: true, .. }
+        ));
+
+        let using = r#"CASE WHEN "active" THEN now()::date END"#;
+        let options = options.using("user", "active", Expr::Raw(using.to_string()));
+        let migration = migrate(schema(Type::Boolean), schema(Type::Date), &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            format!(r#"ALTER TABLE "user" ALTER COLUMN "active" TYPE date USING {using}"#)
+        );
+        // the expression is kept as given, and written for the dialect
+        let Statement::AlterTable(alter) = &migration.statements[0] else {
+            panic!("expected an alter table, got {:?}", migration.statements[0]);
+        };
+        let given = Some(Expr::Raw(using.to_string()));
+        assert!(matches!(
+            &alter.actions[0],
+            AlterAction::AlterColumn {
+                action: AlterColumnAction::SetType { using, .. },
+                ..
+            } if *using == given
+        ));
+
+        let options = MigrationOptions::default().using("user", "active", Expr::Raw(using.into()));
+        let migration = migrate(schema(Type::Boolean), schema(Type::Date), &options).unwrap();
+        assert!(migration.is_empty());
+
+        // MySQL reads `DateTime` columns back as `NaiveDateTime`
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..MigrationOptions::default()
+        };
+        let current = schema(Type::NaiveDateTime);
+        let migration = migrate(current, schema(Type::DateTime), &options).unwrap();
+        assert!(migration.is_empty());
+
+        // and changes the type and nullability by redefining the column
+        let mut desired = schema(Type::I64);
+        desired.tables[0].columns[0].nullable = false;
+        let migration = migrate(schema(Type::I32), desired, &options).unwrap();
+        let sql = |statements: &[Statement]| {
+            statements.iter().map(|s| s.to_sql(Dialect::Mysql)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sql(&migration.statements),
+            vec![r#"ALTER TABLE "user" MODIFY COLUMN "active" BIGINT NOT NULL"#]
+        );
+        assert_eq!(sql(&migration.down), vec![r#"ALTER TABLE "user" MODIFY COLUMN "active" INT"#]);
+    }
+
+    #[test]
+    fn test_serial_to_identity() {
+        let column = Column::new("id", Type::I32)
+            .not_null()
+            .default("nextval('user_id_seq'::regclass)");
+        let current = Schema {
+            tables: vec![Table::new("user").column(column.clone())],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![Table::new("user").column(Column {
+                default: None,
+                generated: Some(Generated::Identity { always: true }),
+                ..column
+            })],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"ALTER TABLE "user" ALTER COLUMN "id" DROP DEFAULT"#,
+                r#"DROP SEQUENCE "user_id_seq""#,
+                r#"ALTER TABLE "user" ALTER COLUMN "id" ADD GENERATED ALWAYS AS IDENTITY"#,
+                r#"SELECT setval(pg_get_serial_sequence('"user"', 'id'), coalesce(max("id"), 0) + 1, false) FROM "user" "#,
+            ]
+        );
+
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..MigrationOptions::default()
+        };
+        let id = Column::new("id", Type::I32).primary_key();
+        let schema = |column: Column| Schema {
+            tables: vec![Table::new("user").column(column)],
+            ..Schema::default()
+        };
+        let identity = id.clone().generated(Generated::Identity { always: false });
+        let migration = migrate(schema(id), schema(identity), &options).unwrap();
+        assert!(!migration.statements.iter().any(|s| matches!(s, Statement::Select(_))));
+    }
+
+    #[test]
+    fn test_default_sequence() {
+        let sequence = |s: &str| default_sequence(&Expr::Raw(s.to_string()));
+        assert_eq!(
+            sequence("nextval('user_id_seq'::regclass)"),
+            Some((None, "user_id_seq".to_string()))
+        );
+        assert_eq!(
+            sequence(r#"nextval('auth."User.id_seq"'::regclass)"#),
+            Some((Some("auth".to_string()), "User.id_seq".to_string()))
+        );
+        assert_eq!(sequence("now()"), None);
+    }
+
+    #[test]
+    fn test_comments() {
+        let column = Column::new("email", Type::Text).not_null();
+        let current = Schema {
+            tables: vec![Table::new("user").column(column.clone())],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![Table::new("user")
+                .comment("Accounts")
+                .column(column.comment("Used to log in"))],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"COMMENT ON TABLE "user" IS 'Accounts'"#,
+                r#"COMMENT ON COLUMN "user"."email" IS 'Used to log in'"#,
+            ]
+        );
+
+        // MySQL creates tables and columns with their comments
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..MigrationOptions::default()
+        };
+        let table = Table::new("team").comment("Groups").column(Column::new("name", Type::Text));
+        let desired = Schema {
+            tables: vec![table.clone().column(Column::new("size", Type::I32).comment("People"))],
+            ..Schema::default()
+        };
+        let migration = migrate(Schema::default(), desired.clone(), &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Mysql),
+            concat!(
+                "CREATE TABLE \"team\" (\n\"name\" TEXT,\n",
+                "\"size\" INT COMMENT 'People'\n) COMMENT = 'Groups'",
+            )
+        );
+        assert_eq!(migration.statements.len(), 1);
+        let current = Schema {
+            tables: vec![table],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Mysql),
+            r#"ALTER TABLE "team" ADD COLUMN "size" INT COMMENT 'People'"#
+        );
+        assert_eq!(migration.statements.len(), 1);
+
+        // commenting a column redefines it, enum variants included
+        let mood = Column::new("mood", Type::Enum("mood".to_string()));
+        let schema = |column: Column| Schema {
+            tables: vec![Table::new("team").column(column)],
+            enums: vec![Enum::new("mood", &["happy", "sad"])],
+            ..Schema::default()
+        };
+        let current = schema(mood.clone());
+        let desired = schema(mood.comment("Usually happy"));
+        let migration = migrate(current, desired, &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Mysql),
+            concat!(
+                r#"ALTER TABLE "team" MODIFY COLUMN "mood" ENUM('happy', 'sad') "#,
+                "COMMENT 'Usually happy'",
+            )
+        );
+        assert_eq!(migration.statements.len(), 1);
+    }
+
+    #[test]
+    fn test_same_names_in_schemas() {
+        let schema = |auth_age: Type, auth_mood: &[&str], score: bool| {
+            let table = |schema: &str, age: Type| Table {
+                schema: Some(schema.to_string()),
+                ..Table::new("user").column(Column::new("age", age))
+            };
+            let mut auth_user = table("auth", auth_age);
+            if score {
+                auth_user = auth_user.column(Column::new("score", Type::I32).not_null());
+            }
+            Schema {
+                tables: vec![table("public", Type::I16), auth_user],
+                enums: vec![
+                    Enum::new("mood", &["happy"]),
+                    Enum::new("mood", auth_mood).schema("auth"),
+                ],
+                views: vec![
+                    View::new("adult", r#"SELECT * FROM public."user""#),
+                    View::new("adult", r#"SELECT * FROM auth."user""#).schema("auth"),
+                ],
+                sequences: vec![
+                    Sequence::new("counter"),
+                    Sequence::new("counter").schema("auth").increment(2),
+                ],
+            }
+        };
+        let options = MigrationOptions::default().backfill_in(Some("auth"), "user", "score", "0");
+        let migration = migrate(
+            schema(Type::I16, &["happy"], false),
+            schema(Type::I32, &["happy", "sad"], true),
+            &options,
+        )
+        .unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        // the public objects of the same names are left alone
+        assert_eq!(
+            sql,
+            vec![
+                r#"ALTER TYPE "auth"."mood" ADD VALUE 'sad' AFTER 'happy'"#,
+                r#"DROP VIEW "auth"."adult""#,
+                r#"ALTER TABLE "auth"."user" ADD COLUMN "score" integer"#,
+                r#"UPDATE "auth"."user" SET "score" = 0 WHERE "score" IS NULL"#,
+                r#"ALTER TABLE "auth"."user" ALTER COLUMN "score" SET NOT NULL"#,
+                r#"ALTER TABLE "auth"."user" ALTER COLUMN "age" TYPE integer USING "age"::integer"#,
+                r#"CREATE VIEW "auth"."adult" AS SELECT * FROM auth."user""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_views() {
+        let table = |typ: Type| {
+            Table::new("user").column(Column::new("age", typ))
+        };
+        let view = View::new("adult", "SELECT * FROM \"user\" WHERE age >= 18");
+        let current = Schema {
+            tables: vec![table(Type::I16)],
+            views: vec![view.clone()],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![table(Type::I32)],
+            views: vec![view],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"DROP VIEW "adult""#,
+                r#"ALTER TABLE "user" ALTER COLUMN "age" TYPE integer USING "age"::integer"#,
+                r#"CREATE VIEW "adult" AS SELECT * FROM "user" WHERE age >= 18"#,
+            ]
+        );
+
+        // views reading a dropped view are dropped first and recreated last, and materialized
+        // views reading changed data are refreshed
+        let views = vec![
+            View::new("adult_name", "SELECT name FROM adult"),
+            View::new("adult_count", "SELECT count(*) FROM adult_name").materialized(),
+            View::new("adult", "SELECT * FROM \"user\" WHERE age >= 18"),
+            View::new("team_size", "SELECT count(*) FROM team").materialized(),
+        ];
+        let team = Table::new("team").column(Column::new("id", Type::I32));
+        let current = Schema {
+            tables: vec![table(Type::I16), team.clone()],
+            views: views.clone(),
+            ..Schema::default()
+        };
+        let mut desired = Schema {
+            tables: vec![
+                table(Type::I32),
+                team.column(Column::new("score", Type::I32).not_null()),
+            ],
+            views,
+            ..Schema::default()
+        };
+        let options = MigrationOptions::default().backfill("team", "score", "0");
+        let migration = migrate(current.clone(), desired.clone(), &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            [
+                r#"DROP MATERIALIZED VIEW "adult_count""#,
+                r#"DROP VIEW "adult_name""#,
+                r#"DROP VIEW "adult""#,
+                r#"ALTER TABLE "user" ALTER COLUMN "age" TYPE integer USING "age"::integer"#,
+                r#"ALTER TABLE "team" ADD COLUMN "score" integer"#,
+                r#"UPDATE "team" SET "score" = 0 WHERE "score" IS NULL"#,
+                r#"ALTER TABLE "team" ALTER COLUMN "score" SET NOT NULL"#,
+                r#"CREATE VIEW "adult" AS SELECT * FROM "user" WHERE age >= 18"#,
+                r#"CREATE VIEW "adult_name" AS SELECT name FROM adult"#,
+                r#"CREATE MATERIALIZED VIEW "adult_count" AS SELECT count(*) FROM adult_name"#,
+                r#"REFRESH MATERIALIZED VIEW "team_size""#,
+            ]
+        );
+
+        // dropping a view drops the views reading it, even if they're unchanged
+        desired.tables = current.tables.clone();
+        desired.views.retain(|v| v.name != "adult");
+        let options = MigrationOptions {
+            allow_destructive: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current, desired, &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql[..3],
+            [
+                r#"DROP MATERIALIZED VIEW "adult_count""#,
+                r#"DROP VIEW "adult_name""#,
+                r#"DROP VIEW "adult""#,
+            ]
+        );
+
+        let current = Schema {
+            views: vec![View::new("adult", "SELECT * FROM \"user\" WHERE age > 18")],
+            ..Schema::default()
+        };
+        let migration = migrate(current, Schema::default(), &MigrationOptions::default()).unwrap();
+        assert!(migration.is_empty());
+        assert!(matches!(
+            migration.debug_results[0],
+            DebugResults::SkippedDropView(_)
+        ));
+    }
+
+    #[test]
+    fn test_sequences() {
+        let sequence = Sequence::new("invoice_number").start(1000);
+        let invoice = Table::new("invoice").column(Column::new("number", Type::I64));
+        let desired = Schema {
+            tables: vec![invoice.clone()],
+            sequences: vec![sequence.clone().owned_by("invoice", "number")],
+            ..Schema::default()
+        };
+        let migration =
+            migrate(Schema::default(), desired.clone(), &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"CREATE SEQUENCE "invoice_number" START WITH 1000"#,
+                "CREATE TABLE \"invoice\" (\n\"number\" bigint\n)",
+                r#"ALTER SEQUENCE "invoice_number" OWNED BY "invoice"."number""#,
+            ]
+        );
+
+        // the owning table is in the sequence's schema
+        let mut desired = desired;
+        desired.default_schema("billing");
+        let migration = migrate(Schema::default(), desired, &MigrationOptions::default()).unwrap();
+        assert_eq!(
+            migration.statements.last().unwrap().to_sql(Dialect::Postgres),
+            r#"ALTER SEQUENCE "billing"."invoice_number" OWNED BY "billing"."invoice"."number""#
+        );
+
+        let current = Schema {
+            tables: vec![invoice.clone()],
+            sequences: vec![sequence
+                .clone()
+                .increment(1)
+                .owned_by("invoice", "number")],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![invoice],
+            sequences: vec![sequence.increment(10)],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER SEQUENCE "invoice_number" INCREMENT BY 10"#
+        );
+    }
+
+    #[test]
+    fn test_primary_key_change() {
+        let column = |name: &str| Column::new(name, Type::I32).not_null();
+        let table = || {
+            Table::new("membership")
+                .column(column("team_id"))
+                .column(column("user_id"))
+        };
+        let mut current = table();
+        current.primary_key = Some(PrimaryKey::new(&["team_id"]));
+        let mut desired = table();
+        desired.primary_key = Some(PrimaryKey::new(&["team_id", "user_id"]));
+        let migration = migrate(
+            Schema {
+                tables: vec![current.clone()],
+                ..Schema::default()
+            },
+            Schema {
+                tables: vec![desired.clone()],
+                ..Schema::default()
+            },
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "membership" DROP CONSTRAINT "membership_pkey", ADD PRIMARY KEY ("team_id", "user_id")"#
+        );
+
+        let migration = migrate(
+            Schema {
+                tables: vec![desired.clone()],
+                ..Schema::default()
+            },
+            Schema {
+                tables: vec![desired],
+                ..Schema::default()
+            },
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert!(migration.is_empty());
+    }
+
+    #[test]
+    fn test_foreign_key_change() {
+        let schema = |fk: ForeignKey| Schema {
+            tables: vec![Table::new("user").column(
+                Column::new("team_id", Type::I32)
+                    .not_null()
+                    .constraint(Constraint::ForeignKey(fk)),
+            )],
+            ..Schema::default()
+        };
+        let fk = ForeignKey::new("team", &["id"]);
+        let migration = migrate(
+            schema(fk.clone()),
+            schema(fk.clone().on_delete(ReferentialAction::Cascade)),
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" DROP CONSTRAINT "user_team_id_fkey", ADD CONSTRAINT "user_team_id_fkey" FOREIGN KEY ("team_id") REFERENCES "team"("id") ON DELETE CASCADE"#
+        );
+
+        let migration = migrate(
+            schema(fk.clone()),
+            schema(fk.on_delete(ReferentialAction::NoAction)),
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert!(migration.is_empty());
+    }
+
+    #[test]
+    fn test_drop_and_replace_foreign_key() {
+        let table = || {
+            Table::new("user").column(Column::new("team_id", Type::I32).not_null())
+        };
+        // as read from the database, with its real name
+        let current = table().constraint(TableConstraint::new(
+            "fk_user_team",
+            &["team_id"],
+            Constraint::ForeignKey(ForeignKey::new("team", &["id"])),
+        ));
+        let mut desired = table();
+        desired.columns[0].constraint =
+            Some(Constraint::ForeignKey(ForeignKey::new("organization", &["id"])));
+
+        let migration = migrate(
+            Schema {
+                tables: vec![current.clone()],
+                ..Schema::default()
+            },
+            Schema {
+                tables: vec![desired],
+                ..Schema::default()
+            },
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" DROP CONSTRAINT "fk_user_team", ADD CONSTRAINT "fk_user_team" FOREIGN KEY ("team_id") REFERENCES "organization"("id")"#
+        );
+
+        let migration = migrate(
+            Schema {
+                tables: vec![current],
+                ..Schema::default()
+            },
+            Schema {
+                tables: vec![table()],
+                ..Schema::default()
+            },
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" DROP CONSTRAINT "fk_user_team""#
+        );
+    }
+
+    #[test]
+    fn test_table_constraints() {
+        let column = |name: &str, constraint: Option<Constraint>| Column {
+            constraint,
+            ..Column::new(name, Type::Text).not_null()
+        };
+        let mut current = Table::new("user")
+            .column(column("email", None))
+            .column(column("name", None));
+        current.constraints = vec![
+            TableConstraint::new("user_email_key", &["email"], Constraint::Unique),
+            TableConstraint::new("name_check", &[], Constraint::check("(length(name) > 0)")),
+        ];
+        // same unique constraint declared on the column, changed check, and one new constraint
+        let desired = Table::new("user")
+            .column(column("email", Some(Constraint::Unique)))
+            .column(column("name", None))
+            .constraint(TableConstraint::new(
+                "name_check",
+                &[],
+                Constraint::check("length(name) > 1"),
+            ))
+            .constraint(TableConstraint::new(
+                "user_name_key",
+                &["name"],
+                Constraint::Unique,
+            ));
+        let migration = migrate(
+            Schema {
+                tables: vec![current],
+                ..Schema::default()
+            },
+            Schema {
+                tables: vec![desired],
+                ..Schema::default()
+            },
+            &MigrationOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TABLE "user" DROP CONSTRAINT "name_check", ADD CONSTRAINT "name_check" CHECK (length(name) > 1), ADD CONSTRAINT "user_name_key" UNIQUE ("name")"#
+        );
+    }
+
+    #[test]
+    fn test_enums() {
+        let table = Table::new("user").column(Column::new("mood", Type::Enum("mood".to_string())));
+        let desired = Schema {
+            tables: vec![table.clone()],
+            enums: vec![Enum::new("mood", &["happy", "ok", "sad"])],
+            ..Schema::default()
+        };
+        let migration =
+            migrate(Schema::default(), desired.clone(), &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"CREATE TYPE "mood" AS ENUM ('happy', 'ok', 'sad')"#,
+                "CREATE TABLE \"user\" (\n\"mood\" \"mood\"\n)",
+            ]
+        );
+
+        let current = Schema {
+            tables: vec![table.clone()],
+            enums: vec![Enum::new("mood", &["ok", "sad", "angry"])],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired.clone(), &MigrationOptions::default()).unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Postgres),
+            r#"ALTER TYPE "mood" ADD VALUE 'happy' BEFORE 'ok'"#
+        );
+        assert!(matches!(
+            migration.debug_results[..],
+            [DebugResults::TablesIdentical(_), DebugResults::SkippedEnumVariantRemoval(_)]
+        ));
+
+        // MySQL redefines the columns of the enum
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..MigrationOptions::default()
+        };
+        let current = Schema {
+            tables: vec![table.clone()],
+            enums: vec![Enum::new("mood", &["happy", "ok"])],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Mysql))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![r#"ALTER TABLE "user" MODIFY COLUMN "mood" ENUM('happy', 'ok', 'sad')"#]
+        );
+
+        // enums are named with their schema
+        let desired = Schema {
+            tables: vec![table.schema("auth")],
+            enums: vec![Enum::new("mood", &["ok"]).schema("auth")],
+            ..Schema::default()
+        };
+        let migration =
+            migrate(Schema::default(), desired, &MigrationOptions::default()).unwrap();
+        assert_eq!(
+            migration.statements.last().unwrap().to_sql(Dialect::Postgres),
+            "CREATE TABLE \"auth\".\"user\" (\n\"mood\" \"auth\".\"mood\"\n)"
+        );
+    }
+
     #[test]
     fn test_topological_sort_statements() {
         let empty_schema = Schema::default();
         let mut schema_with_tables = Schema::default();
 
         // Create dependent tables: User depends on Team
-        let team_table = Table::new("team").column(Column {
-            name: "id".to_string(),
-            typ: Type::I32,
-            nullable: false,
-            primary_key: true,
-            default: None,
-            constraint: None,
-        });
+        let team_table = Table::new("team").column(Column::new("id", Type::I32).primary_key());
 
         let user_table = Table::new("user")
-            .column(Column {
-                name: "id".to_string(),
-                typ: Type::I32,
-                nullable: false,
-                primary_key: true,
-                default: None,
-                constraint: None,
-            })
-            .column(Column {
-                name: "team_id".to_string(),
-                typ: Type::I32,
-                nullable: false,
-                primary_key: false,
-                default: None,
-                constraint: Some(Constraint::ForeignKey(ForeignKey {
-                    table: "team".to_string(),
-                    columns: vec!["id".to_string()],
-                })),
-            });
+            .column(Column::new("id", Type::I32).primary_key())
+            .column(
+                Column::new("team_id", Type::I32)
+                    .not_null()
+                    .constraint(Constraint::ForeignKey(ForeignKey::new("team", &["id"]))),
+            );
 
         schema_with_tables.tables.push(user_table);
         schema_with_tables.tables.push(team_table);
@@ -426,4 +2419,174 @@ mod tests {
             "Team table should be created before User table"
         );
     }
+
+    #[test]
+    fn test_foreign_key_cycle() {
+        let column = |name: &str, references: Option<&str>| match references {
+            Some(table) => Column::new(name, Type::I32)
+                .constraint(Constraint::ForeignKey(ForeignKey::new(table, &["id"]))),
+            None => Column::new(name, Type::I32).primary_key(),
+        };
+        let tables = vec![
+            Table::new("user")
+                .column(column("id", None))
+                .column(column("team_id", Some("team"))),
+            Table::new("team")
+                .column(column("id", None))
+                .column(column("owner_id", Some("user"))),
+            Table::new("membership")
+                .column(column("id", None))
+                .column(column("user_id", Some("user"))),
+        ];
+        let schema = Schema {
+            tables,
+            ..Schema::default()
+        };
+        let migration =
+            migrate(Schema::default(), schema.clone(), &MigrationOptions::default()).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                concat!(
+                    "CREATE TABLE \"user\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"team_id\" integer\n)",
+                ),
+                concat!(
+                    "CREATE TABLE \"team\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"owner_id\" integer REFERENCES \"user\"(\"id\")\n)",
+                ),
+                concat!(
+                    "CREATE TABLE \"membership\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"user_id\" integer REFERENCES \"user\"(\"id\")\n)",
+                ),
+                concat!(
+                    r#"ALTER TABLE "user" ADD CONSTRAINT "user_team_id_fkey""#,
+                    r#" FOREIGN KEY ("team_id") REFERENCES "team"("id")"#,
+                ),
+            ]
+        );
+        // dropping the constraint first lets the tables be dropped
+        assert!(matches!(&migration.down[0], Statement::AlterTable(a) if a.name == "user"));
+
+        let options = MigrationOptions {
+            allow_destructive: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(schema, Schema::default(), &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"DROP TABLE "membership""#,
+                r#"ALTER TABLE "team" DROP CONSTRAINT "team_owner_id_fkey""#,
+                r#"DROP TABLE "user""#,
+                r#"DROP TABLE "team""#,
+            ]
+        );
+        let down = migration
+            .down
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        // the restored cycle is broken the same way it is when it's first created
+        assert_eq!(
+            down,
+            vec![
+                concat!(
+                    "CREATE TABLE \"team\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"owner_id\" integer\n)",
+                ),
+                concat!(
+                    "CREATE TABLE \"user\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"team_id\" integer REFERENCES \"team\"(\"id\")\n)",
+                ),
+                concat!(
+                    "CREATE TABLE \"membership\" (\n\"id\" integer NOT NULL PRIMARY KEY,\n",
+                    "\"user_id\" integer REFERENCES \"user\"(\"id\")\n)",
+                ),
+                concat!(
+                    r#"ALTER TABLE "team" ADD CONSTRAINT "team_owner_id_fkey""#,
+                    r#" FOREIGN KEY ("owner_id") REFERENCES "user"("id")"#,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_schemas() {
+        let id = || Column::new("id", Type::I32).primary_key();
+        let user_id = Column::new("user_id", Type::I32).not_null().constraint(
+            Constraint::ForeignKey(ForeignKey::new("users", &["id"]).schema("public")),
+        );
+        let current = Schema {
+            tables: vec![
+                Table::new("users").schema("public").column(id()),
+                Table::new("export").schema("archive").column(id()),
+            ],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("users").schema("audit").column(id()).column(user_id),
+                Table::new("users").column(id()),
+            ],
+            ..Schema::default()
+        };
+
+        let migration = migrate(current.clone(), desired.clone(), &MigrationOptions::default())
+            .unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                r#"CREATE SCHEMA IF NOT EXISTS "audit""#.to_string(),
+                r#"CREATE TABLE "audit"."users" (
+"id" integer NOT NULL PRIMARY KEY,
+"user_id" integer NOT NULL REFERENCES "public"."users"("id")
+)"#
+                .to_string(),
+            ]
+        );
+        assert!(migration.debug_results.iter().any(|d| matches!(
+            d,
+            DebugResults::SkippedDropSchema(name) if name == "archive"
+        )));
+
+        let options = MigrationOptions {
+            allow_destructive: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current, desired, &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .skip(2)
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(sql, vec![r#"DROP TABLE "archive"."export""#, r#"DROP SCHEMA "archive""#]);
+    }
+
+    #[test]
+    fn test_default_schema() {
+        let mut schema = Schema {
+            tables: vec![Table::new("users"), Table::new("log").schema("audit")],
+            ..Schema::default()
+        };
+        schema.default_schema("public");
+        assert_eq!(schema.tables[0].schema.as_deref(), Some("public"));
+        assert_eq!(schema.tables[1].schema.as_deref(), Some("audit"));
+    }
 }
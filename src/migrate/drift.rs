@@ -0,0 +1,354 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use super::{display_name, migrate, DebugResults, MigrationOptions, Statement};
+use crate::query::{AlterAction, AlterColumnAction, AlterTable};
+use crate::schema::{Schema, Table, Type};
+use crate::{Dialect, ToSql};
+
+/// Options for `check_drift`.
+#[derive(Debug, Clone)]
+pub struct DriftOptions {
+    /// Tables managed outside sqlmo, by name or by `schema.name`. Includes the migration history
+    /// table by default.
+    pub ignore_tables: Vec<String>,
+    /// See `MigrationOptions::default_schema`.
+    pub default_schema: Option<String>,
+}
+
+impl Default for DriftOptions {
+    fn default() -> Self {
+        DriftOptions {
+            ignore_tables: vec!["_sqlmo_migrations".to_string()],
+            default_schema: None,
+        }
+    }
+}
+
+impl DriftOptions {
+    pub fn ignore_table(mut self, table: &str) -> Self {
+        self.ignore_tables.push(table.to_string());
+        self
+    }
+
+    fn ignores(&self, table: &Table) -> bool {
+        let default_schema = self.default_schema.as_deref().unwrap_or("public");
+        self.ignore_tables.iter().any(|ignored| match ignored.split_once('.') {
+            Some((schema, name)) => {
+                table.schema.as_deref().unwrap_or(default_schema) == schema && table.name == name
+            }
+            None => table.name == *ignored,
+        })
+    }
+}
+
+/// A difference between the database and the desired schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    MissingTable(String),
+    ExtraTable(String),
+    MissingColumn { table: String, column: String },
+    ExtraColumn { table: String, column: String },
+    TypeMismatch {
+        table: String,
+        column: String,
+        expected: Type,
+        actual: Type,
+    },
+    NullabilityMismatch {
+        table: String,
+        column: String,
+        expected_nullable: bool,
+    },
+    /// Any other difference, as the SQL that would fix it, or a description.
+    Other { name: String, description: String },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Drift::MissingTable(table) => write!(f, "missing table {}", table),
+            Drift::ExtraTable(table) => write!(f, "extra table {}", table),
+            Drift::MissingColumn { table, column } => {
+                write!(f, "missing column {}.{}", table, column)
+            }
+            Drift::ExtraColumn { table, column } => write!(f, "extra column {}.{}", table, column),
+            Drift::TypeMismatch {
+                table,
+                column,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{}.{} has type {}, expected {}",
+                table,
+                column,
+                actual.to_sql(Dialect::Postgres),
+                expected.to_sql(Dialect::Postgres)
+            ),
+            Drift::NullabilityMismatch {
+                table,
+                column,
+                expected_nullable,
+            } => write!(
+                f,
+                "{}.{} is {}, expected {}",
+                table,
+                column,
+                if *expected_nullable { "NOT NULL" } else { "nullable" },
+                if *expected_nullable { "nullable" } else { "NOT NULL" }
+            ),
+            Drift::Other { name, description } => write!(f, "{}: {}", name, description),
+        }
+    }
+}
+
+/// The differences between the database and the desired schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DriftReport {
+    pub drifts: Vec<Drift>,
+}
+
+impl DriftReport {
+    pub fn is_empty(&self) -> bool {
+        self.drifts.is_empty()
+    }
+
+    /// An error listing the drift, if there is any.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{}", self))
+        }
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No schema drift");
+        }
+        write!(f, "Schema drift:")?;
+        for drift in &self.drifts {
+            write!(f, "\n  - {}", drift)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the schema read from the database to the desired schema, e.g. to fail CI when they
+/// differ.
+pub fn check_drift(
+    database: Schema,
+    desired: Schema,
+    options: &DriftOptions,
+) -> Result<DriftReport> {
+    let strip = |mut schema: Schema| {
+        schema.tables.retain(|t| !options.ignores(t));
+        schema
+    };
+    let database = strip(database);
+    let desired = strip(desired);
+    let default_schema = options.default_schema.as_deref().unwrap_or("public");
+    let find = |schema: &Option<String>, name: &str| {
+        database.tables.iter().find(|t| {
+            t.name == name
+                && t.schema.as_deref().unwrap_or(default_schema)
+                    == schema.as_deref().unwrap_or(default_schema)
+        })
+    };
+
+    let migration = migrate(
+        database.clone(),
+        desired.clone(),
+        &MigrationOptions {
+            default_schema: options.default_schema.clone(),
+            ..MigrationOptions::default()
+        },
+    )?;
+    let mut drifts = vec![];
+    for statement in &migration.statements {
+        match statement {
+            Statement::CreateTable(c) => {
+                drifts.push(Drift::MissingTable(display_name(&c.schema, &c.name)))
+            }
+            Statement::AlterTable(alter) => {
+                let Some(table) = find(&alter.schema, &alter.name) else {
+                    continue;
+                };
+                drifts.extend(alter_drift(table, alter));
+            }
+            // these accompany other statements
+            Statement::Update(_) | Statement::Backfill(_) | Statement::Select(_) => {}
+            statement => drifts.push(Drift::Other {
+                name: statement.table_name().to_string(),
+                description: statement.to_sql(Dialect::Postgres),
+            }),
+        }
+    }
+    for result in migration.debug_results {
+        let drift = match result {
+            DebugResults::TablesIdentical(_) => continue,
+            DebugResults::RiskyTypeConversion { skipped: false, .. } => continue,
+            DebugResults::SkippedDropTable(table) => Drift::ExtraTable(table),
+            DebugResults::RiskyTypeConversion { table, column, from, to, .. } => {
+                Drift::TypeMismatch {
+                    table,
+                    column,
+                    expected: to,
+                    actual: from,
+                }
+            }
+            DebugResults::SkippedEnumVariantRemoval(name) => Drift::Other {
+                name,
+                description: "enum has extra variants".to_string(),
+            },
+            DebugResults::SkippedAddColumn { table, column } => {
+                Drift::MissingColumn { table, column }
+            }
+            DebugResults::SkippedColumnRebuild { table, column } => Drift::Other {
+                name: format!("{}.{}", table, column),
+                description: "generated column differs".to_string(),
+            },
+            DebugResults::SkippedDropView(name) => Drift::Other {
+                name,
+                description: "extra view".to_string(),
+            },
+            DebugResults::SkippedDropSequence(name) => Drift::Other {
+                name,
+                description: "extra sequence".to_string(),
+            },
+            DebugResults::SkippedDropSchema(name) => Drift::Other {
+                name,
+                description: "extra schema".to_string(),
+            },
+        };
+        drifts.push(drift);
+    }
+    // migrate() leaves columns that aren't in the desired schema alone
+    for desired_table in &desired.tables {
+        let Some(table) = find(&desired_table.schema, &desired_table.name) else {
+            continue;
+        };
+        for column in &table.columns {
+            if !desired_table.columns.iter().any(|c| c.name == column.name) {
+                drifts.push(Drift::ExtraColumn {
+                    table: display_name(&desired_table.schema, &table.name),
+                    column: column.name.clone(),
+                });
+            }
+        }
+    }
+    Ok(DriftReport { drifts })
+}
+
+fn alter_drift(table: &Table, alter: &AlterTable) -> Vec<Drift> {
+    let name = display_name(&alter.schema, &alter.name);
+    let mut drifts = vec![];
+    for action in &alter.actions {
+        let drift = match action {
+            AlterAction::AddColumn { column } => Drift::MissingColumn {
+                table: name.clone(),
+                column: column.name.clone(),
+            },
+            AlterAction::AlterColumn {
+                name: column,
+                action: column_action,
+            } => {
+                // the column is missing, and already reported
+                let Some(current) = table.columns.iter().find(|c| &c.name == column) else {
+                    continue;
+                };
+                match column_action {
+                    AlterColumnAction::SetType { typ, .. } => Drift::TypeMismatch {
+                        table: name.clone(),
+                        column: column.clone(),
+                        expected: typ.clone(),
+                        actual: current.typ.clone(),
+                    },
+                    AlterColumnAction::SetNullable(nullable) => Drift::NullabilityMismatch {
+                        table: name.clone(),
+                        column: column.clone(),
+                        expected_nullable: *nullable,
+                    },
+                    _ => other(alter, action),
+                }
+            }
+            _ => other(alter, action),
+        };
+        drifts.push(drift);
+    }
+    drifts
+}
+
+fn other(alter: &AlterTable, action: &AlterAction) -> Drift {
+    let single = AlterTable {
+        actions: vec![action.clone()],
+        ..alter.clone()
+    };
+    Drift::Other {
+        name: display_name(&alter.schema, &alter.name),
+        description: single.to_sql(Dialect::Postgres),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Column;
+
+    #[test]
+    fn test_drift() {
+        let column = |name: &str, typ: Type, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, typ)
+        };
+        let database = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("name", Type::Text, true))
+                    .column(column("age", Type::Boolean, true))
+                    .column(column("legacy", Type::Text, true)),
+                Table::new("audit_log"),
+                Table::new("old_user").schema("archive"),
+                Table::new("_sqlmo_migrations"),
+            ],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("name", Type::Text, false))
+                    .column(column("age", Type::Date, true))
+                    .column(column("email", Type::Text, true))
+                    .column(column("joined", Type::Date, false)),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+
+        let report = check_drift(database.clone(), desired.clone(), &DriftOptions::default());
+        let report = report.unwrap();
+        assert_eq!(
+            report.to_string(),
+            "Schema drift:
+  - missing table team
+  - user.name is nullable, expected NOT NULL
+  - missing column user.email
+  - user.age has type boolean, expected date
+  - missing column user.joined
+  - extra table audit_log
+  - extra table archive.old_user
+  - archive: extra schema
+  - extra column user.legacy"
+        );
+        assert!(report.into_result().is_err());
+
+        // tables without a schema are in the default schema
+        let options = DriftOptions::default().ignore_table("public.audit_log");
+        let report = check_drift(database, desired, &options).unwrap();
+        assert!(!report.drifts.contains(&Drift::ExtraTable("audit_log".to_string())));
+    }
+}
@@ -0,0 +1,529 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+
+use super::{transactions, Migration, Statement};
+use crate::query::{AlterAction, AlterTable};
+use crate::{Dialect, ToSql};
+
+/// The file layout of a migration tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationLayout {
+    /// `{timestamp}_{name}.up.sql` and `{timestamp}_{name}.down.sql`
+    Sqlx,
+    /// `V{version}__{name}.sql`, numbered after the existing migrations. Refinery has no down
+    /// migrations, and runs each migration in a transaction.
+    Refinery,
+    /// `{YYYY-MM-DD-HHMMSS}_{name}/up.sql` and `down.sql`
+    Diesel,
+    /// `{timestamp}_{name}.sql`, with `-- migrate:up` and `-- migrate:down` sections.
+    Dbmate,
+}
+
+/// Writes a `Migration` as the up and down files of a migration tool.
+#[derive(Debug, Clone)]
+pub struct MigrationWriter {
+    pub layout: MigrationLayout,
+    pub dialect: Dialect,
+    /// Defaults to `Migration::name`.
+    pub name: Option<String>,
+    /// Seconds since the unix epoch. Defaults to now.
+    pub timestamp: Option<u64>,
+}
+
+impl MigrationWriter {
+    pub fn new(layout: MigrationLayout) -> Self {
+        MigrationWriter {
+            layout,
+            dialect: Dialect::Postgres,
+            name: None,
+            timestamp: None,
+        }
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// The paths, relative to `dir`, and contents of the migration files. `dir` is only read to
+    /// number Refinery migrations. Migrations with statements that can't run in a transaction
+    /// are marked to run outside one, and group their other statements into transactions.
+    pub fn files(&self, dir: &Path, migration: &Migration) -> Result<Vec<(PathBuf, String)>> {
+        let name = sanitize(&self.name.clone().unwrap_or_else(|| migration.name()));
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        });
+        let (date, time) = utc(timestamp);
+        if self.dialect == Dialect::Postgres {
+            check_concurrently(&migration.statements)?;
+            check_concurrently(&migration.down)?;
+        }
+        check_batched(&migration.statements)?;
+        let up = migration.up_sql(self.dialect);
+        let down = migration.down_sql(self.dialect);
+        let up_transactional = is_transactional(&migration.statements, self.dialect);
+        let down_transactional = is_transactional(&migration.down, self.dialect);
+        let files = match self.layout {
+            MigrationLayout::Sqlx => {
+                let no_transaction = |transactional| match transactional {
+                    true => "",
+                    false => "-- no-transaction\n",
+                };
+                vec![
+                    (
+                        format!("{date}{time}_{name}.up.sql").into(),
+                        format!("{}{up}", no_transaction(up_transactional)),
+                    ),
+                    (
+                        format!("{date}{time}_{name}.down.sql").into(),
+                        format!("{}{down}", no_transaction(down_transactional)),
+                    ),
+                ]
+            }
+            MigrationLayout::Refinery => {
+                if !up_transactional {
+                    bail!(
+                        "Migration {name} has statements that can't run in a transaction, which \
+                         Refinery runs every migration in"
+                    );
+                }
+                let version = refinery_version(dir)? + 1;
+                vec![(format!("V{version}__{name}.sql").into(), up)]
+            }
+            MigrationLayout::Diesel => {
+                let date = format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]);
+                let dir = PathBuf::from(format!("{date}-{time}_{name}"));
+                let mut files = vec![(dir.join("up.sql"), up), (dir.join("down.sql"), down)];
+                if !(up_transactional && down_transactional) {
+                    let metadata = "run_in_transaction = false\n".to_string();
+                    files.push((dir.join("metadata.toml"), metadata));
+                }
+                files
+            }
+            MigrationLayout::Dbmate => {
+                let options = |transactional| match transactional {
+                    true => "",
+                    false => " transaction:false",
+                };
+                vec![(
+                    format!("{date}{time}_{name}.sql").into(),
+                    format!(
+                        "-- migrate:up{}\n{up}\n-- migrate:down{}\n{down}",
+                        options(up_transactional),
+                        options(down_transactional),
+                    ),
+                )]
+            }
+        };
+        Ok(files)
+    }
+
+    /// Write the migration files into `dir`, returning their paths.
+    pub fn write(&self, dir: &Path, migration: &Migration) -> Result<Vec<PathBuf>> {
+        let mut paths = vec![];
+        for (path, contents) in self.files(dir, migration)? {
+            let path = dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("creating {}", parent.display()))?;
+            }
+            fs::write(&path, contents).with_context(|| format!("writing {}", path.display()))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// The highest version of the `V{version}__{name}.sql` files in `dir`.
+fn refinery_version(dir: &Path) -> Result<u64> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut version = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let name = entry?.file_name();
+        let Some(rest) = name.to_str().and_then(|n| n.strip_prefix('V')) else {
+            continue;
+        };
+        if let Some(Ok(v)) = rest.split_once("__").map(|(v, _)| v.parse::<u64>()) {
+            version = version.max(v);
+        }
+    }
+    Ok(version)
+}
+
+/// `YYYYMMDD` and `HHMMSS` in UTC.
+fn utc(timestamp: u64) -> (String, String) {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // civil from days, https://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        format!("{year:04}{month:02}{day:02}"),
+        format!("{:02}{:02}{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60),
+    )
+}
+
+fn sanitize(name: &str) -> String {
+    let name = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() {
+        "migration".to_string()
+    } else {
+        name
+    }
+}
+
+/// Migration tools send a file to Postgres as one query, which runs in a transaction even when
+/// the file is marked not to, so `CREATE INDEX CONCURRENTLY` has to be on its own.
+fn check_concurrently(statements: &[Statement]) -> Result<()> {
+    let concurrently = statements
+        .iter()
+        .find(|s| matches!(s, Statement::CreateIndex(c) if c.concurrently));
+    match concurrently {
+        Some(statement) if statements.len() > 1 => bail!(
+            "{} has to be the only statement of its migration file, as Postgres runs the \
+             statements of a file in one transaction. Apply the migration with apply_migration \
+             in sqlmo_sqlx instead.",
+            statement.to_sql(Dialect::Postgres)
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// A batched backfill commits each batch as it goes, which a script can't do.
+fn check_batched(statements: &[Statement]) -> Result<()> {
+    match statements.iter().find(|s| matches!(s, Statement::Backfill(b) if b.is_batched())) {
+        Some(Statement::Backfill(b)) => bail!(
+            "The backfill of {}.{} is batched, which a migration file can't run. Apply the \
+             migration with apply_migration in sqlmo_sqlx instead, or leave \
+             MigrationOptions::backfill_batch_size unset.",
+            b.table,
+            b.column
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Whether the statements that do anything in `dialect` can run in a transaction.
+fn is_transactional(statements: &[Statement], dialect: Dialect) -> bool {
+    statements
+        .iter()
+        .filter(|s| s.is_supported(dialect))
+        .all(Statement::is_transactional)
+}
+
+/// The statements as a script. Migration tools run a script in a transaction, unless it's
+/// marked not to because of a statement that can't run in one, in which case the script begins
+/// and commits the transactions of the other statements itself.
+fn script(statements: &[Statement], dialect: Dialect, online: bool) -> String {
+    let statements = statements
+        .iter()
+        .filter(|s| s.is_supported(dialect))
+        .cloned()
+        .collect::<Vec<_>>();
+    let own_transactions = !is_transactional(&statements, dialect);
+    let mut buf = String::new();
+    for (transactional, batch) in transactions(&statements, online) {
+        let wrap = own_transactions && transactional;
+        if wrap {
+            buf.push_str("BEGIN;\n");
+        }
+        for (_, statement) in batch {
+            buf.push_str(&statement.to_sql(dialect));
+            buf.push_str(";\n");
+        }
+        if wrap {
+            buf.push_str("COMMIT;\n");
+        }
+    }
+    buf
+}
+
+/// Describe the changes to a table, e.g. `add_team_id_to_user`.
+fn describe_alter(alter: &AlterTable) -> String {
+    let columns = |f: fn(&AlterAction) -> Option<&str>| {
+        alter.actions.iter().map(f).collect::<Option<Vec<_>>>()
+    };
+    let added = columns(|a| match a {
+        AlterAction::AddColumn { column } => Some(&column.name),
+        _ => None,
+    });
+    let dropped = columns(|a| match a {
+        AlterAction::DropColumn { name } => Some(name),
+        _ => None,
+    });
+    match (added, dropped) {
+        (Some(added), _) => format!("add_{}_to_{}", added.join("_"), alter.name),
+        (_, Some(dropped)) => format!("drop_{}_from_{}", dropped.join("_"), alter.name),
+        _ => format!("alter_{}", alter.name),
+    }
+}
+
+impl Migration {
+    /// A name describing the migration, e.g. `create_team_and_add_team_id_to_user`.
+    pub fn name(&self) -> String {
+        let mut parts: Vec<(String, &str)> = vec![];
+        for statement in &self.statements {
+            let (part, table) = match statement {
+                Statement::CreateSchema(s) => (format!("create_{}_schema", s.name), ""),
+                Statement::DropSchema(s) => (format!("drop_{}_schema", s.name), ""),
+                Statement::CreateEnum(s) => (format!("create_{}_enum", s.name), ""),
+                Statement::AlterEnum(s) => (format!("add_{}_to_{}", s.value, s.name), ""),
+                Statement::DropEnum(s) => (format!("drop_{}_enum", s.name), ""),
+                Statement::CreateTable(s) => (format!("create_{}", s.name), s.name.as_str()),
+                Statement::DropTable(s) => (format!("drop_{}", s.name), s.name.as_str()),
+                Statement::AlterTable(s) => (describe_alter(s), s.name.as_str()),
+                Statement::CreateIndex(s) => (format!("create_{}", s.name), ""),
+                Statement::DropIndex(s) => (format!("drop_{}", s.name), ""),
+                Statement::CreateView(s) => (format!("create_{}_view", s.name), ""),
+                Statement::DropView(s) => (format!("drop_{}_view", s.name), ""),
+                Statement::CreateSequence(s) => (format!("create_{}", s.name), ""),
+                Statement::AlterSequence(s) => (format!("alter_{}", s.name), ""),
+                Statement::DropSequence(s) => (format!("drop_{}", s.name), ""),
+                Statement::RebuildTable(s) => {
+                    (format!("rebuild_{}", s.table.name), s.table.name.as_str())
+                }
+                // these accompany the statements above
+                Statement::Update(_)
+                | Statement::Backfill(_)
+                | Statement::LockTable(_)
+                | Statement::CreateTrigger(_)
+                | Statement::DropTrigger(_)
+                | Statement::Select(_)
+                | Statement::CommentOn(_)
+                | Statement::RefreshMaterializedView(_)
+                | Statement::Pragma(_) => continue,
+            };
+            if !parts.iter().any(|(p, _)| *p == part) {
+                parts.push((part, table));
+            }
+        }
+        // a table that's created, or has columns added, doesn't also need `alter_{table}`
+        let is_alter = |(part, table): &(String, &str)| *part == format!("alter_{table}");
+        let described = parts
+            .iter()
+            .filter(|p| !p.1.is_empty() && !is_alter(p))
+            .map(|p| p.1)
+            .collect::<Vec<_>>();
+        parts.retain(|p| !(is_alter(p) && described.contains(&p.1)));
+        let parts = parts.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+        match parts.len() {
+            0 => "migration".to_string(),
+            1..=3 => parts.join("_and_"),
+            _ => format!("{}_and_more", parts[..2].join("_and_")),
+        }
+    }
+
+    /// The statements as a SQL script.
+    pub fn up_sql(&self, dialect: Dialect) -> String {
+        script(&self.statements, dialect, self.online)
+    }
+
+    /// The down statements as a SQL script, preceded by a comment for each step that can't be
+    /// reversed.
+    pub fn down_sql(&self, dialect: Dialect) -> String {
+        let mut buf = String::new();
+        for irreversible in &self.irreversible {
+            buf.push_str("-- irreversible: ");
+            buf.push_str(&irreversible.reason);
+            buf.push('\n');
+        }
+        buf.push_str(&script(&self.down, dialect, false));
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrate::{migrate, MigrationOptions};
+    use crate::query::Expr;
+    use crate::schema::{Column, Enum, Index, Schema, Table};
+    use crate::Type;
+
+    fn migration() -> Migration {
+        let id = |name: &str| Column::new(name, Type::I32);
+        let current = Schema {
+            tables: vec![Table::new("user").column(id("id"))],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("user").column(id("id")).column(id("team_id")),
+                Table::new("team").column(id("id")),
+            ],
+            ..Schema::default()
+        };
+        migrate(current, desired, &MigrationOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(migration().name(), "create_team_and_add_team_id_to_user");
+        assert_eq!(utc(0), ("19700101".to_string(), "000000".to_string()));
+        assert_eq!(utc(1709251199), ("20240229".to_string(), "235959".to_string()));
+    }
+
+    #[test]
+    fn test_layouts() {
+        let dir = Path::new("migrations");
+        let migration = migration();
+        let writer = |layout| MigrationWriter::new(layout).timestamp(1700000000);
+        let paths = |layout| {
+            writer(layout)
+                .files(dir, &migration)
+                .unwrap()
+                .into_iter()
+                .map(|(path, _)| path.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let name = "create_team_and_add_team_id_to_user";
+        assert_eq!(
+            paths(MigrationLayout::Sqlx),
+            vec![
+                format!("20231114221320_{name}.up.sql"),
+                format!("20231114221320_{name}.down.sql"),
+            ]
+        );
+        assert_eq!(paths(MigrationLayout::Refinery), vec![format!("V1__{name}.sql")]);
+        assert_eq!(
+            paths(MigrationLayout::Diesel),
+            vec![
+                format!("2023-11-14-221320_{name}/up.sql"),
+                format!("2023-11-14-221320_{name}/down.sql"),
+            ]
+        );
+        let files = writer(MigrationLayout::Dbmate).name("Add teams!").files(dir, &migration);
+        let (path, contents) = files.unwrap().pop().unwrap();
+        assert_eq!(path, PathBuf::from("20231114221320_add_teams.sql"));
+        assert_eq!(
+            contents,
+            concat!(
+                "-- migrate:up\n",
+                "CREATE TABLE \"team\" (\n\"id\" integer\n);\n",
+                "ALTER TABLE \"user\" ADD COLUMN \"team_id\" integer;\n",
+                "\n-- migrate:down\n",
+                "ALTER TABLE \"user\" DROP COLUMN \"team_id\";\n",
+                "DROP TABLE \"team\";\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_no_transaction() {
+        let mood = |variants: &[&str]| Enum {
+            schema: None,
+            name: "mood".to_string(),
+            variants: variants.iter().map(|v| v.to_string()).collect(),
+        };
+        let table = Table::new("user").column(Column::new("id", Type::I32));
+        let current = Schema {
+            tables: vec![table.clone()],
+            enums: vec![mood(&["happy"])],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![table.column(Column::new("mood", Type::Enum("mood".to_string())))],
+            enums: vec![mood(&["happy", "sad"])],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current.clone(), desired.clone(), &options).unwrap();
+        assert!(matches!(migration.statements.as_slice(), [Statement::AlterTable(_)]));
+        let migration = migrate(current.clone(), desired, &MigrationOptions::default()).unwrap();
+        let dir = Path::new("migrations");
+        let writer = |layout| MigrationWriter::new(layout).timestamp(1700000000).name("sad");
+        let files = writer(MigrationLayout::Sqlx).files(dir, &migration).unwrap();
+        assert_eq!(
+            files[0].1,
+            concat!(
+                "-- no-transaction\n",
+                "ALTER TYPE \"mood\" ADD VALUE 'sad' AFTER 'happy';\n",
+                "BEGIN;\n",
+                "ALTER TABLE \"user\" ADD COLUMN \"mood\" \"mood\";\n",
+                "COMMIT;\n",
+            )
+        );
+        // the down migration can run in a transaction
+        assert!(!files[1].1.contains("no-transaction"));
+        let (_, dbmate) = writer(MigrationLayout::Dbmate).files(dir, &migration).unwrap().remove(0);
+        assert!(dbmate.starts_with("-- migrate:up transaction:false\n"));
+        assert!(dbmate.contains("\n-- migrate:down\n"));
+        let diesel = writer(MigrationLayout::Diesel).files(dir, &migration).unwrap();
+        assert_eq!(diesel[2].1, "run_in_transaction = false\n");
+        assert!(writer(MigrationLayout::Refinery).files(dir, &migration).is_err());
+        // MySQL declares enums inline, so there's no enum statement to keep out of a transaction
+        let mysql = writer(MigrationLayout::Sqlx).dialect(Dialect::Mysql);
+        let files = mysql.files(dir, &migration).unwrap();
+        assert_eq!(
+            files[0].1,
+            "ALTER TABLE \"user\" ADD COLUMN \"mood\" ENUM('happy', 'sad');\n"
+        );
+
+        // CREATE INDEX CONCURRENTLY can't share a file
+        let mut desired = current.clone();
+        desired.tables[0].indexes.push(Index {
+            name: "user_id_idx".to_string(),
+            columns: vec!["id".to_string()],
+        });
+        let options = MigrationOptions {
+            online: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current.clone(), desired.clone(), &options).unwrap();
+        let files = writer(MigrationLayout::Sqlx).files(dir, &migration).unwrap();
+        assert!(files[0].1.starts_with("-- no-transaction\nCREATE INDEX CONCURRENTLY"));
+        desired.tables[0].columns.push(Column::new("age", Type::I32));
+        let migration = migrate(current, desired, &options).unwrap();
+        assert!(writer(MigrationLayout::Sqlx).files(dir, &migration).is_err());
+
+        // batches commit as they go, which a file can't
+        let table = Table::new("user").column(Column::new("id", Type::I64).primary_key());
+        let current = Schema {
+            tables: vec![table.clone()],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![table.column(Column::new("age", Type::I32).not_null())],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            backfill_batch_size: Some(1000),
+            ..MigrationOptions::default()
+        }
+        .backfill("user", "age", Expr::Raw("0".to_string()));
+        let migration = migrate(current, desired, &options).unwrap();
+        assert!(writer(MigrationLayout::Dbmate).files(dir, &migration).is_err());
+    }
+}
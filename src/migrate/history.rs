@@ -0,0 +1,156 @@
+use super::Migration;
+use crate::query::{CreateTable, Insert, Select};
+use crate::schema::{Column, Table};
+use crate::{Dialect, Type};
+
+/// A migration recorded in the history table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AppliedMigration {
+    pub name: String,
+    /// `Migration::checksum` of the migration when it was applied.
+    pub checksum: String,
+    /// Set by the database when the migration is recorded.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub applied_at: Option<String>,
+    pub execution_time_ms: i64,
+}
+
+/// The table recording which migrations have been applied, `_sqlmo_migrations` by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationHistory {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl Default for MigrationHistory {
+    fn default() -> Self {
+        MigrationHistory {
+            schema: None,
+            name: "_sqlmo_migrations".to_string(),
+        }
+    }
+}
+
+impl MigrationHistory {
+    pub fn new(name: &str) -> Self {
+        MigrationHistory {
+            schema: None,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    pub fn table(&self) -> Table {
+        let column = |name: &str, typ: Type| Column::new(name, typ).not_null();
+        Table {
+            schema: self.schema.clone(),
+            ..Table::new(&self.name)
+        }
+        // MySQL can't index TEXT columns
+        .column(Column::new("name", Type::Varchar(255)).primary_key())
+        .column(column("checksum", Type::Varchar(64)))
+        // whole seconds, as MySQL's `CURRENT_TIMESTAMP` only fills a `DATETIME` without a fraction
+        .column(column("applied_at", Type::TimestampTz(0)).default("CURRENT_TIMESTAMP"))
+        .column(column("execution_time_ms", Type::I64))
+    }
+
+    pub fn create_table(&self) -> CreateTable {
+        CreateTable::from_table(&self.table()).if_not_exists()
+    }
+
+    /// Record an applied migration. Bind the name, checksum and execution time, in that order.
+    pub fn record(&self, dialect: Dialect) -> Insert {
+        let mut insert = Insert::new(&self.name)
+            .columns(&["name", "checksum", "execution_time_ms"])
+            .placeholder_for_each_column(dialect);
+        insert.schema = self.schema.clone();
+        insert
+    }
+
+    /// List the applied migrations, oldest first, with the columns of `AppliedMigration`.
+    /// `applied_at` is cast to text.
+    pub fn list(&self, dialect: Dialect) -> Select {
+        let text = match dialect {
+            Dialect::Mysql => "CHAR",
+            Dialect::Postgres | Dialect::Sqlite => "TEXT",
+        };
+        let mut select = Select::default()
+            .select_raw(r#""name""#)
+            .select_raw(r#""checksum""#)
+            .select_raw(format!(r#"CAST("applied_at" AS {text}) AS "applied_at""#))
+            .select_raw(r#""execution_time_ms""#)
+            .from(&self.name)
+            .order_asc("applied_at")
+            .order_asc("name");
+        if let Some(from) = &mut select.from {
+            from.schema = self.schema.clone();
+        }
+        select
+    }
+}
+
+impl Migration {
+    /// A checksum of the migration's SQL, to detect applied migrations that were edited. Not a
+    /// cryptographic hash.
+    pub fn checksum(&self, dialect: Dialect) -> String {
+        checksum(&self.up_sql(dialect))
+    }
+}
+
+/// 64-bit FNV-1a, as hex.
+pub fn checksum(sql: &str) -> String {
+    let hash = sql.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToSql;
+
+    #[test]
+    fn test_history() {
+        let history = MigrationHistory::default().schema("meta");
+        assert_eq!(
+            history.create_table().to_sql(Dialect::Postgres),
+            r#"CREATE TABLE IF NOT EXISTS "meta"."_sqlmo_migrations" (
+"name" character varying(255) NOT NULL PRIMARY KEY,
+"checksum" character varying(64) NOT NULL,
+"applied_at" timestamp(0) with time zone NOT NULL DEFAULT CURRENT_TIMESTAMP,
+"execution_time_ms" bigint NOT NULL
+)"#
+        );
+        assert_eq!(
+            history.create_table().to_sql(Dialect::Mysql),
+            r#"CREATE TABLE IF NOT EXISTS "meta"."_sqlmo_migrations" (
+"name" VARCHAR(255) NOT NULL PRIMARY KEY,
+"checksum" VARCHAR(64) NOT NULL,
+"applied_at" DATETIME(0) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+"execution_time_ms" BIGINT NOT NULL
+)"#
+        );
+        assert_eq!(
+            history.record(Dialect::Sqlite).to_sql(Dialect::Sqlite),
+            concat!(
+                r#"INSERT OR ABORT INTO "meta"."_sqlmo_migrations""#,
+                r#" ("name", "checksum", "execution_time_ms") VALUES (?, ?, ?)"#,
+            )
+        );
+        assert_eq!(
+            history.list(Dialect::Mysql).to_sql(Dialect::Mysql),
+            concat!(
+                r#"SELECT "name", "checksum", CAST("applied_at" AS CHAR) AS "applied_at","#,
+                r#" "execution_time_ms" FROM "meta"."_sqlmo_migrations""#,
+                "  ORDER BY applied_at ASC, name ASC",
+            )
+        );
+        assert_eq!(checksum(""), "cbf29ce484222325");
+    }
+}
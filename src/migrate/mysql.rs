@@ -0,0 +1,115 @@
+use super::{table_key, Statement};
+use crate::query::{AlterAction, AlterColumnAction, AlterEnum, AlterTable};
+use crate::schema::{Schema, Type};
+
+/// MySQL changes the type or nullability of a column by redefining it, so those actions become a
+/// `MODIFY COLUMN` of the column as it is in `after`, the schema the statements migrate from
+/// `before`. Enums are declared inline, so the values added to one redefine its columns too.
+pub(super) fn mysql_statements(
+    statements: Vec<Statement>,
+    before: &Schema,
+    after: &Schema,
+    default_schema: &str,
+) -> Vec<Statement> {
+    let mut altered_enums = vec![];
+    let mut mysql = vec![];
+    for statement in statements {
+        match statement {
+            Statement::AlterTable(a) => {
+                mysql.push(Statement::AlterTable(modify_columns(a, after, default_schema)));
+            }
+            Statement::AlterEnum(e) => {
+                let (schema, name) = table_key(&e.schema, &e.name, default_schema);
+                let key = (schema.to_string(), name.to_string());
+                if !altered_enums.contains(&key) {
+                    mysql.extend(modify_enum_columns(&e, before, after, default_schema));
+                    altered_enums.push(key);
+                }
+            }
+            statement => mysql.push(statement),
+        }
+    }
+    mysql
+}
+
+/// Redefine the columns of `e` that exist before the migration.
+fn modify_enum_columns(
+    e: &AlterEnum,
+    before: &Schema,
+    after: &Schema,
+    default_schema: &str,
+) -> Vec<Statement> {
+    let key = table_key(&e.schema, &e.name, default_schema);
+    let mut statements = vec![];
+    for table in &after.tables {
+        let enums = after.enums_for(table);
+        let uses_enum = |typ: &Type| {
+            enums.iter().any(|e| {
+                typ.enum_name() == Some(&e.name)
+                    && table_key(&e.schema, &e.name, default_schema) == key
+            })
+        };
+        let existing = before
+            .tables
+            .iter()
+            .find(|t| {
+                table_key(&t.schema, &t.name, default_schema)
+                    == table_key(&table.schema, &table.name, default_schema)
+            })
+            .map_or(&[][..], |t| &t.columns[..]);
+        let actions = table
+            .columns
+            .iter()
+            .filter(|c| uses_enum(&c.typ) && existing.iter().any(|x| x.name == c.name))
+            .map(|c| AlterAction::ModifyColumn { column: c.clone() })
+            .collect::<Vec<_>>();
+        if !actions.is_empty() {
+            statements.push(Statement::AlterTable(AlterTable {
+                schema: table.schema.clone(),
+                name: table.name.clone(),
+                actions,
+                enums,
+            }));
+        }
+    }
+    statements
+}
+
+fn modify_columns(alter: AlterTable, after: &Schema, default_schema: &str) -> AlterTable {
+    let key = table_key(&alter.schema, &alter.name, default_schema);
+    let Some(table) = after
+        .tables
+        .iter()
+        .find(|t| table_key(&t.schema, &t.name, default_schema) == key)
+    else {
+        return alter;
+    };
+    let mut actions = vec![];
+    for action in alter.actions {
+        let column = match &action {
+            AlterAction::AlterColumn {
+                name,
+                action: AlterColumnAction::SetType { .. } | AlterColumnAction::SetNullable(_),
+            } => table.columns.iter().find(|c| &c.name == name),
+            _ => None,
+        };
+        let Some(column) = column else {
+            actions.push(action);
+            continue;
+        };
+        // the type and nullability are both in the one definition
+        let modified = actions.iter().any(|a| {
+            matches!(a, AlterAction::ModifyColumn { column: c } if c.name == column.name)
+        });
+        if !modified {
+            actions.push(AlterAction::ModifyColumn {
+                column: column.clone(),
+            });
+        }
+    }
+    AlterTable {
+        actions,
+        enums: after.enums_for(table),
+        ..alter
+    }
+}
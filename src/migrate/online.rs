@@ -0,0 +1,374 @@
+use std::collections::HashMap;
+
+use super::{backfill_column, table_key, Statement, TableKey};
+use crate::query::{
+    AlterAction, AlterColumnAction, AlterTable, CommentOn, CreateTrigger, DropTrigger, Expr,
+    LockTable,
+};
+use crate::schema::{Column, Constraint, Conversion, Table, Type, View};
+use crate::util::SqlExtension;
+use crate::Dialect;
+
+/// The check constraint that proves a column has no nulls before it's set `NOT NULL`.
+pub(super) fn not_null_check_name(table: &str, column: &str) -> String {
+    format!("{table}_{column}_not_null")
+}
+
+/// The column a new type is backfilled into, before it replaces the original.
+fn swap_column_name(column: &str) -> String {
+    format!("{column}__sqlmo_new")
+}
+
+/// The trigger, and its function, keeping the new column in sync during the backfill.
+fn sync_trigger_name(table: &str, column: &str) -> String {
+    format!("{table}_{column}__sqlmo_sync")
+}
+
+/// Rewrite the statements altering existing tables, so that blocking locks are only held
+/// briefly. New tables are empty, so their statements are left alone.
+pub(super) fn online_statements(
+    statements: Vec<Statement>,
+    current: &HashMap<TableKey, &Table>,
+    desired: &HashMap<TableKey, &Table>,
+    views: &[View],
+    default_schema: &str,
+    batch_size: Option<u64>,
+) -> Vec<Statement> {
+    let mut online = vec![];
+    for statement in statements {
+        match statement {
+            Statement::CreateIndex(c)
+                if current.contains_key(&table_key(&c.schema, &c.table, default_schema)) =>
+            {
+                online.push(Statement::CreateIndex(c.concurrently()));
+            }
+            Statement::AlterTable(a) => {
+                let key = table_key(&a.schema, &a.name, default_schema);
+                match (current.get(&key), desired.get(&key)) {
+                    (Some(current_table), Some(desired_table)) => {
+                        let steps =
+                            online_alter(&a, current_table, desired_table, views, batch_size);
+                        online.extend(steps);
+                    }
+                    _ => online.push(Statement::AlterTable(a)),
+                }
+            }
+            statement => online.push(statement),
+        }
+    }
+    online
+}
+
+fn alter(alter: &AlterTable, actions: Vec<AlterAction>) -> Statement {
+    Statement::AlterTable(AlterTable {
+        schema: alter.schema.clone(),
+        name: alter.name.clone(),
+        actions,
+        enums: alter.enums.clone(),
+    })
+}
+
+/// Whether nothing else in the table, or a view, refers to the column, so it can be swapped for a
+/// copy, or dropped by SQLite.
+pub(super) fn is_unreferenced(table: &Table, column: &str, views: &[View]) -> bool {
+    let Some(current) = table.columns.iter().find(|c| c.name == column) else {
+        return false;
+    };
+    let contains = |columns: &[String]| columns.iter().any(|c| c == column);
+    current.constraint.is_none()
+        && current.generated.is_none()
+        && !contains(&table.primary_key_columns())
+        && !table.constraints.iter().any(|c| contains(&c.columns))
+        && !table.indexes.iter().any(|i| contains(&i.columns))
+        && !views.iter().any(|v| v.reads_column(table.schema.as_deref(), &table.name, column))
+}
+
+fn online_alter(
+    a: &AlterTable,
+    current: &Table,
+    desired: &Table,
+    views: &[View],
+    batch_size: Option<u64>,
+) -> Vec<Statement> {
+    let swapped = a
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            // binary compatible types are changed in place, without a rewrite
+            AlterAction::AlterColumn {
+                name,
+                action: AlterColumnAction::SetType { conversion, .. },
+            } if *conversion != Some(Conversion::BinaryCompatible)
+                && is_unreferenced(current, name, views) =>
+            {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut actions = vec![];
+    let mut after = vec![];
+    // constraints on swapped columns are added once the swap is done
+    let mut after_swap = vec![];
+    for action in &a.actions {
+        match action {
+            AlterAction::AlterColumn {
+                name,
+                action: AlterColumnAction::SetType { typ, using, .. },
+            } if swapped.contains(&name.as_str()) => {
+                let Some(column) = desired.columns.iter().find(|c| &c.name == name) else {
+                    continue;
+                };
+                after.extend(swap(a, current, column, typ, using.as_ref(), batch_size));
+            }
+            // the swap creates the column as desired
+            AlterAction::AlterColumn { name, .. } if swapped.contains(&name.as_str()) => {}
+            AlterAction::AlterColumn {
+                name,
+                action: AlterColumnAction::SetNullable(false),
+            } => {
+                let (check, steps) = set_not_null(a, name);
+                actions.push(check);
+                after.extend(steps);
+            }
+            AlterAction::AddConstraint { columns, .. }
+                if columns.iter().any(|c| swapped.contains(&c.as_str())) =>
+            {
+                after_swap.push(action.clone());
+            }
+            AlterAction::AddConstraint {
+                name,
+                columns,
+                constraint: constraint @ (Constraint::ForeignKey(_) | Constraint::Check(_)),
+                not_valid: false,
+            } => {
+                actions.push(AlterAction::AddConstraint {
+                    name: name.clone(),
+                    columns: columns.clone(),
+                    constraint: constraint.clone(),
+                    not_valid: true,
+                });
+                after.push(alter(a, vec![AlterAction::validate_constraint(name.clone())]));
+            }
+            action => actions.push(action.clone()),
+        }
+    }
+    let mut statements = vec![];
+    if !actions.is_empty() {
+        statements.push(alter(a, actions));
+    }
+    statements.extend(after);
+    if !after_swap.is_empty() {
+        statements.push(alter(a, after_swap));
+    }
+    statements
+}
+
+/// `SET NOT NULL` scans the table while blocking reads and writes, unless a validated check
+/// constraint already proves there are no nulls. Returns the action adding that constraint, and
+/// the statements that validate it, set the column `NOT NULL`, and drop it.
+fn set_not_null(a: &AlterTable, column: &str) -> (AlterAction, Vec<Statement>) {
+    let name = not_null_check_name(&a.name, column);
+    let mut check = String::new();
+    check.push_quoted(column);
+    check.push_str(" IS NOT NULL");
+    let add = AlterAction::AddConstraint {
+        name: name.clone(),
+        columns: vec![column.to_string()],
+        constraint: Constraint::Check(Expr::Raw(check)),
+        not_valid: true,
+    };
+    let steps = vec![
+        alter(a, vec![AlterAction::validate_constraint(name.clone())]),
+        alter(a, vec![AlterAction::set_nullable(column.to_string(), false)]),
+        alter(a, vec![AlterAction::drop_constraint(name)]),
+    ];
+    (add, steps)
+}
+
+/// Change the type of `column` by backfilling a new column, then swapping it for the original.
+/// A trigger keeps the new column in sync with the rows written during the backfill, so the
+/// table is only locked to drop the trigger and swap the columns. The statements from the lock to
+/// the rename have to run in one transaction, as `apply_migration` in sqlmo_sqlx does.
+fn swap(
+    a: &AlterTable,
+    current: &Table,
+    column: &Column,
+    typ: &Type,
+    using: Option<&Expr>,
+    batch_size: Option<u64>,
+) -> Vec<Statement> {
+    let new_name = swap_column_name(&column.name);
+    let value = match using {
+        Some(using) => using.clone(),
+        None => {
+            let mut cast = String::new();
+            cast.push_quoted(&column.name);
+            cast.push_str("::");
+            cast.push_sql(typ, Dialect::Postgres);
+            Expr::Raw(cast)
+        }
+    };
+    let new_column = Column::new(&new_name, typ.clone());
+    let backfill = backfill_column(current, &new_name, value.clone(), batch_size);
+    let trigger = sync_trigger_name(&a.name, &column.name);
+    let sync = CreateTrigger::new(&trigger, &a.name, &new_name, value);
+    let mut statements = vec![
+        alter(a, vec![AlterAction::AddColumn { column: new_column }]),
+        Statement::CreateTrigger(sync.schema(a.schema.clone())),
+        Statement::Backfill(backfill.schema(a.schema.clone())),
+        Statement::LockTable(LockTable::new(&a.name).schema(a.schema.clone())),
+        Statement::DropTrigger(DropTrigger::new(&trigger, &a.name).schema(a.schema.clone())),
+        alter(a, vec![AlterAction::DropColumn {
+            name: column.name.clone(),
+        }]),
+        alter(a, vec![AlterAction::rename_column(new_name, column.name.clone())]),
+    ];
+    if let Some(default) = &column.default {
+        statements.push(alter(
+            a,
+            vec![AlterAction::set_default(column.name.clone(), default.clone())],
+        ));
+    }
+    if !column.nullable {
+        let (check, steps) = set_not_null(a, &column.name);
+        statements.push(alter(a, vec![check]));
+        statements.extend(steps);
+    }
+    if column.comment.is_some() {
+        statements.push(Statement::CommentOn(
+            CommentOn::column(&a.name, column.clone()).schema(a.schema.clone()),
+        ));
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrate::{migrate, MigrationOptions, Safety};
+    use crate::schema::{ForeignKey, Index, Schema};
+    use crate::ToSql;
+
+    #[test]
+    fn test_online() {
+        let column = |name: &str, typ: Type, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, typ)
+        };
+        let current = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("age", Type::I32, true))
+                    .column(column("email", Type::Text, true))
+                    .column(column("team_id", Type::I32, true)),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("age", Type::I64, false))
+                    .column(column("email", Type::Text, false))
+                    .column(
+                        column("team_id", Type::I32, true)
+                            .constraint(Constraint::ForeignKey(ForeignKey::new("team", &["id"]))),
+                    )
+                    .index(Index {
+                        name: "user_email_idx".to_string(),
+                        columns: vec!["email".to_string()],
+                    }),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            online: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current.clone(), desired.clone(), &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                concat!(
+                    r#"ALTER TABLE "user" ADD CONSTRAINT "user_email_not_null""#,
+                    r#" CHECK ("email" IS NOT NULL) NOT VALID,"#,
+                    r#" ADD CONSTRAINT "user_team_id_fkey" FOREIGN KEY ("team_id")"#,
+                    r#" REFERENCES "team"("id") NOT VALID"#,
+                ),
+                r#"ALTER TABLE "user" ADD COLUMN "age__sqlmo_new" bigint"#,
+                concat!(
+                    r#"CREATE FUNCTION "user_age__sqlmo_sync"() RETURNS trigger"#,
+                    r#" LANGUAGE plpgsql AS $sqlmo$ BEGIN NEW."age__sqlmo_new" :="#,
+                    r#" (SELECT "age"::bigint FROM (SELECT NEW.*) AS "user"); RETURN NEW;"#,
+                    r#" END $sqlmo$; CREATE TRIGGER "user_age__sqlmo_sync" BEFORE INSERT OR"#,
+                    r#" UPDATE ON "user" FOR EACH ROW EXECUTE FUNCTION "user_age__sqlmo_sync"()"#,
+                ),
+                concat!(
+                    r#"UPDATE "user" SET "age__sqlmo_new" = "age"::bigint"#,
+                    r#" WHERE "age__sqlmo_new" IS NULL"#,
+                ),
+                r#"LOCK TABLE "user" IN ACCESS EXCLUSIVE MODE"#,
+                concat!(
+                    r#"DROP TRIGGER "user_age__sqlmo_sync" ON "user";"#,
+                    r#" DROP FUNCTION "user_age__sqlmo_sync"()"#,
+                ),
+                r#"ALTER TABLE "user" DROP COLUMN "age""#,
+                r#"ALTER TABLE "user" RENAME COLUMN "age__sqlmo_new" TO "age""#,
+                concat!(
+                    r#"ALTER TABLE "user" ADD CONSTRAINT "user_age_not_null""#,
+                    r#" CHECK ("age" IS NOT NULL) NOT VALID"#,
+                ),
+                r#"ALTER TABLE "user" VALIDATE CONSTRAINT "user_age_not_null""#,
+                r#"ALTER TABLE "user" ALTER COLUMN "age" SET NOT NULL"#,
+                r#"ALTER TABLE "user" DROP CONSTRAINT "user_age_not_null""#,
+                r#"ALTER TABLE "user" VALIDATE CONSTRAINT "user_email_not_null""#,
+                r#"ALTER TABLE "user" ALTER COLUMN "email" SET NOT NULL"#,
+                r#"ALTER TABLE "user" DROP CONSTRAINT "user_email_not_null""#,
+                r#"ALTER TABLE "user" VALIDATE CONSTRAINT "user_team_id_fkey""#,
+                r#"CREATE INDEX CONCURRENTLY "user_email_idx" ON "user" USING BTREE ("email")"#,
+            ]
+        );
+        let report = migration.risk_report();
+        assert!(report.at_least(Safety::Dangerous).next().is_none());
+
+        // a column a view reads can't be dropped, so its type is changed in place
+        let view = View::new("adult", r#"SELECT age FROM "user" WHERE age >= 18"#);
+        let with_view = |schema: &Schema| Schema {
+            views: vec![view.clone()],
+            ..schema.clone()
+        };
+        let migration = migrate(with_view(&current), with_view(&desired), &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(sql[0], r#"DROP VIEW "adult""#);
+        assert!(sql[1].contains(r#"ALTER COLUMN "age" TYPE bigint USING "age"::bigint"#));
+        assert!(!sql.iter().any(|s| s.contains("age__sqlmo_new")));
+
+        // other dialects ignore `online`
+        let mysql = MigrationOptions {
+            dialect: Some(Dialect::Mysql),
+            ..options.clone()
+        };
+        let migration = migrate(current.clone(), desired.clone(), &mysql).unwrap();
+        assert!(!migration.online);
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Mysql))
+            .collect::<Vec<_>>();
+        assert!(!sql.iter().any(|s| s.contains("sqlmo") || s.contains("CONCURRENTLY")));
+
+        let blocking = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        assert_eq!(blocking.risk_report().safety(), Safety::Dangerous);
+    }
+}
@@ -0,0 +1,519 @@
+use crate::query::{
+    AlterAction, AlterColumnAction, AlterSequence, AlterTable, CommentOn, CommentTarget,
+    CreateEnum, CreateIndex, CreateSchema, CreateSequence, CreateTable, CreateView, DropEnum,
+    DropIndex, DropSchema, DropSequence, DropTable, DropView, Expr,
+};
+use crate::schema::{Generated, PrimaryKey, Schema, SequenceOptions, Table};
+use crate::{Dialect, ToSql};
+
+use super::{column_constraints, order_creates, table_key, Statement};
+
+/// A step of a migration that its down statements can't fully undo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Irreversible {
+    pub statement: Statement,
+    pub reason: String,
+}
+
+/// The statements undoing `statements`, in the order they run, restoring definitions from
+/// `current`, the schema the migration starts from.
+pub(super) fn reverse_statements(
+    statements: &[Statement],
+    current: &Schema,
+    default_schema: &str,
+    dialect: Dialect,
+) -> (Vec<Statement>, Vec<Irreversible>) {
+    let reverse = Reverse {
+        current,
+        default_schema,
+        dialect,
+    };
+    let dropped = statements
+        .iter()
+        .filter_map(|s| match s {
+            Statement::DropTable(d) => Some(table_key(&d.schema, &d.name, default_schema)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut down = vec![];
+    let mut irreversible = vec![];
+    for statement in statements.iter().rev() {
+        // the foreign keys dropped before their table, to break a cycle, are restored with it
+        if let Statement::AlterTable(a) = statement {
+            if dropped.contains(&table_key(&a.schema, &a.name, default_schema)) {
+                continue;
+            }
+        }
+        let mut reasons = vec![];
+        down.extend(reverse.statement(statement, &mut reasons));
+        irreversible.extend(reasons.into_iter().map(|reason| Irreversible {
+            statement: statement.clone(),
+            reason,
+        }));
+    }
+    // restored tables can reference each other, so they're created in dependency order, after
+    // the sequences their defaults use
+    if let Some(first) = down.iter().position(|s| matches!(s, Statement::CreateTable(_))) {
+        let mut creates = vec![];
+        let mut sequences = vec![];
+        let mut rest = vec![];
+        for statement in down.split_off(first) {
+            match statement {
+                Statement::CreateTable(c) => creates.push(c),
+                Statement::CreateSequence(_) => sequences.push(statement),
+                statement => rest.push(statement),
+            }
+        }
+        let (creates, deferred) = order_creates(creates, default_schema);
+        down.extend(sequences);
+        down.extend(creates.into_iter().map(Statement::CreateTable));
+        down.extend(deferred);
+        down.extend(rest);
+    }
+    (down, irreversible)
+}
+
+struct Reverse<'a> {
+    current: &'a Schema,
+    default_schema: &'a str,
+    dialect: Dialect,
+}
+
+impl Reverse<'_> {
+    fn same(&self, a: (&Option<String>, &str), b: (&Option<String>, &str)) -> bool {
+        table_key(a.0, a.1, self.default_schema) == table_key(b.0, b.1, self.default_schema)
+    }
+
+    fn table(&self, schema: &Option<String>, name: &str) -> Option<&Table> {
+        self.current
+            .tables
+            .iter()
+            .find(|t| self.same((&t.schema, &t.name), (schema, name)))
+    }
+
+    /// Recreate a dropped table, with its indexes, comments, and the sequences dropped with it.
+    /// The sequences are owned by the table once it exists again.
+    fn restore_table(&self, table: &Table) -> Vec<Statement> {
+        let mut statements = vec![];
+        let mut owners = vec![];
+        let owned = self.current.sequences.iter().filter(|s| {
+            let owner = s.options.owned_by.as_ref();
+            owner.is_some_and(|o| self.same((&s.schema, &o.table), (&table.schema, &table.name)))
+        });
+        for sequence in owned {
+            let mut create = CreateSequence::from_sequence(sequence);
+            let owned_by = create.options.owned_by.take();
+            statements.push(Statement::CreateSequence(create));
+            owners.push(Statement::AlterSequence(AlterSequence {
+                schema: sequence.schema.clone(),
+                name: sequence.name.clone(),
+                options: SequenceOptions {
+                    owned_by,
+                    ..SequenceOptions::default()
+                },
+            }));
+        }
+        let mut create = CreateTable::from_table(table);
+        create.enums = self.current.enums_for(table);
+        statements.push(Statement::CreateTable(create));
+        for index in &table.indexes {
+            statements.push(Statement::CreateIndex(CreateIndex::from_index(table, index)));
+        }
+        // MySQL creates the table with its comments
+        if self.dialect != Dialect::Mysql {
+            if table.comment.is_some() {
+                let comment = CommentOn::table(&table.name, table.comment.clone());
+                statements.push(Statement::CommentOn(comment.schema(table.schema.clone())));
+            }
+            for column in table.columns.iter().filter(|c| c.comment.is_some()) {
+                let comment = CommentOn::column(&table.name, column.clone());
+                statements.push(Statement::CommentOn(comment.schema(table.schema.clone())));
+            }
+        }
+        statements.extend(owners);
+        statements
+    }
+
+    fn statement(&self, statement: &Statement, reasons: &mut Vec<String>) -> Vec<Statement> {
+        let current = self.current;
+        match statement {
+            Statement::CreateSchema(s) => vec![Statement::DropSchema(DropSchema::new(&s.name))],
+            Statement::DropSchema(s) => {
+                vec![Statement::CreateSchema(CreateSchema::new(&s.name))]
+            }
+            Statement::CreateEnum(e) => vec![Statement::DropEnum(DropEnum {
+                schema: e.schema.clone(),
+                name: e.name.clone(),
+            })],
+            Statement::DropEnum(d) => current
+                .enums
+                .iter()
+                .find(|e| self.same((&e.schema, &e.name), (&d.schema, &d.name)))
+                .map(|e| Statement::CreateEnum(CreateEnum::from_enum(e)))
+                .into_iter()
+                .collect(),
+            Statement::AlterEnum(e) => {
+                reasons.push(format!("Postgres can't remove the enum value {}", e.value));
+                vec![]
+            }
+            Statement::CreateTable(c) => vec![Statement::DropTable(DropTable {
+                schema: c.schema.clone(),
+                name: c.name.clone(),
+            })],
+            Statement::DropTable(d) => {
+                reasons.push(format!("dropping {} loses its rows", d.name));
+                let Some(table) = self.table(&d.schema, &d.name) else {
+                    return vec![];
+                };
+                self.restore_table(table)
+            }
+            Statement::CreateIndex(c) => vec![Statement::DropIndex(DropIndex {
+                schema: c.schema.clone(),
+                name: c.name.clone(),
+                table: c.table.clone(),
+            })],
+            Statement::DropIndex(d) => {
+                let table = self.table(&d.schema, &d.table);
+                let index = table.and_then(|t| {
+                    let index = t.indexes.iter().find(|i| i.name == d.name)?;
+                    Some((t, index))
+                });
+                let Some((table, index)) = index else {
+                    reasons.push(format!("the definition of {} is unknown", d.name));
+                    return vec![];
+                };
+                vec![Statement::CreateIndex(CreateIndex {
+                    schema: d.schema.clone(),
+                    ..CreateIndex::from_index(table, index)
+                })]
+            }
+            Statement::AlterTable(a) => {
+                // a table created by the migration is dropped as a whole, but foreign keys added
+                // after it was created can reference other tables that are dropped first
+                let Some(table) = self.table(&a.schema, &a.name) else {
+                    let actions = a
+                        .actions
+                        .iter()
+                        .filter_map(|action| match action {
+                            AlterAction::AddConstraint { name, .. } => {
+                                Some(AlterAction::drop_constraint(name.clone()))
+                            }
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>();
+                    if actions.is_empty() {
+                        return vec![];
+                    }
+                    return vec![Statement::AlterTable(AlterTable {
+                        actions,
+                        ..a.clone()
+                    })];
+                };
+                let actions = a
+                    .actions
+                    .iter()
+                    .rev()
+                    .flat_map(|action| reverse_action(table, action, self.dialect, reasons))
+                    .collect::<Vec<_>>();
+                if actions.is_empty() {
+                    return vec![];
+                }
+                vec![Statement::AlterTable(AlterTable {
+                    schema: a.schema.clone(),
+                    name: a.name.clone(),
+                    actions,
+                    enums: current.enums_for(table),
+                })]
+            }
+            // backfills of new columns, locks, the triggers syncing swapped columns, and resetting
+            // sequences, don't need undoing
+            Statement::Update(_)
+            | Statement::Backfill(_)
+            | Statement::LockTable(_)
+            | Statement::CreateTrigger(_)
+            | Statement::DropTrigger(_)
+            | Statement::Select(_)
+            | Statement::RefreshMaterializedView(_) => vec![],
+            // SQLite rebuilds are planned from the reversed statements, see `sqlite_statements`
+            Statement::RebuildTable(_) | Statement::Pragma(_) => vec![],
+            Statement::CommentOn(c) => {
+                let Some(table) = self.table(&c.schema, &c.table) else {
+                    return vec![];
+                };
+                let comment = match &c.target {
+                    CommentTarget::Table => CommentOn::table(&table.name, table.comment.clone()),
+                    CommentTarget::Column(column) => {
+                        let Some(column) = table.columns.iter().find(|c| c.name == column.name)
+                        else {
+                            return vec![];
+                        };
+                        let enums = self.current.enums_for(table);
+                        CommentOn::column(&table.name, column.clone()).enums(enums)
+                    }
+                };
+                vec![Statement::CommentOn(comment.schema(c.schema.clone()))]
+            }
+            Statement::CreateView(c) => {
+                let view = current
+                    .views
+                    .iter()
+                    .find(|v| self.same((&v.schema, &v.name), (&c.schema, &c.name)));
+                match view {
+                    Some(view) if c.or_replace => {
+                        vec![Statement::CreateView(CreateView::from_view(view).or_replace())]
+                    }
+                    _ => vec![Statement::DropView(DropView {
+                        schema: c.schema.clone(),
+                        name: c.name.clone(),
+                        materialized: c.materialized,
+                    })],
+                }
+            }
+            Statement::DropView(d) => current
+                .views
+                .iter()
+                .find(|v| self.same((&v.schema, &v.name), (&d.schema, &d.name)))
+                .map(|view| Statement::CreateView(CreateView::from_view(view)))
+                .into_iter()
+                .collect(),
+            Statement::CreateSequence(c) => vec![Statement::DropSequence(DropSequence {
+                schema: c.schema.clone(),
+                name: c.name.clone(),
+            })],
+            Statement::AlterSequence(a) => {
+                let Some(sequence) = current
+                    .sequences
+                    .iter()
+                    .find(|s| self.same((&s.schema, &s.name), (&a.schema, &a.name)))
+                else {
+                    return vec![];
+                };
+                let options = restore_options(&a.options, &sequence.options);
+                if options.is_empty() {
+                    return vec![];
+                }
+                vec![Statement::AlterSequence(AlterSequence {
+                    schema: a.schema.clone(),
+                    name: a.name.clone(),
+                    options,
+                })]
+            }
+            Statement::DropSequence(d) => {
+                reasons.push(format!("recreating {} restarts it", d.name));
+                current
+                    .sequences
+                    .iter()
+                    .find(|s| self.same((&s.schema, &s.name), (&d.schema, &d.name)))
+                    .map(|sequence| {
+                        Statement::CreateSequence(CreateSequence::from_sequence(sequence))
+                    })
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+}
+
+/// The current values of the options changed by `changed`. Options the current sequence leaves
+/// to the database default can't be restored.
+fn restore_options(changed: &SequenceOptions, current: &SequenceOptions) -> SequenceOptions {
+    fn restore<T: Clone>(changed: &Option<T>, current: &Option<T>) -> Option<T> {
+        changed.as_ref().and(current.clone())
+    }
+    SequenceOptions {
+        typ: restore(&changed.typ, &current.typ),
+        start: restore(&changed.start, &current.start),
+        increment: restore(&changed.increment, &current.increment),
+        min_value: restore(&changed.min_value, &current.min_value),
+        max_value: restore(&changed.max_value, &current.max_value),
+        cycle: restore(&changed.cycle, &current.cycle),
+        owned_by: restore(&changed.owned_by, &current.owned_by),
+    }
+}
+
+fn reverse_action(
+    table: &Table,
+    action: &AlterAction,
+    dialect: Dialect,
+    reasons: &mut Vec<String>,
+) -> Vec<AlterAction> {
+    use AlterColumnAction::*;
+    let column = |name: &str| table.columns.iter().find(|c| c.name == name);
+    match action {
+        AlterAction::AddColumn { column } => vec![AlterAction::DropColumn {
+            name: column.name.clone(),
+        }],
+        AlterAction::DropColumn { name } => {
+            reasons.push(format!("dropping {}.{} loses its values", table.name, name));
+            column(name)
+                .map(|c| AlterAction::AddColumn { column: c.clone() })
+                .into_iter()
+                .collect()
+        }
+        AlterAction::RenameColumn { name, new_name } => {
+            vec![AlterAction::rename_column(new_name.clone(), name.clone())]
+        }
+        AlterAction::ModifyColumn { column: modified } => match column(&modified.name) {
+            Some(current) => vec![AlterAction::ModifyColumn {
+                column: current.clone(),
+            }],
+            None => vec![],
+        },
+        AlterAction::AlterColumn { name, action } => {
+            // columns added by the migration are dropped as a whole
+            let Some(current) = column(name) else {
+                return vec![];
+            };
+            let action = match action {
+                SetType { typ, .. } => {
+                    if current.typ.conversion_to(typ).is_risky() {
+                        reasons.push(format!(
+                            "converting {}.{} from {} to {} can lose data",
+                            table.name,
+                            name,
+                            current.typ.to_sql(dialect),
+                            typ.to_sql(dialect)
+                        ));
+                    }
+                    SetType {
+                        typ: current.typ.clone(),
+                        using: typ.conversion_using(name, &current.typ).map(Expr::Raw),
+                        conversion: Some(typ.conversion_to(&current.typ)),
+                    }
+                }
+                SetNullable(_) => SetNullable(current.nullable),
+                SetDefault(_) | DropDefault => match &current.default {
+                    Some(default) => SetDefault(default.clone()),
+                    None => DropDefault,
+                },
+                AddIdentity { .. } => DropIdentity,
+                SetIdentity { .. } => match &current.generated {
+                    Some(Generated::Identity { always }) => SetIdentity { always: *always },
+                    _ => return vec![],
+                },
+                DropIdentity => match &current.generated {
+                    Some(Generated::Identity { always }) => AddIdentity { always: *always },
+                    _ => return vec![],
+                },
+                SetExpression(_) => match &current.generated {
+                    Some(Generated::Stored(expr)) => SetExpression(expr.clone()),
+                    _ => return vec![],
+                },
+                DropExpression => {
+                    reasons.push(format!(
+                        "{}.{} can't be made a generated column again",
+                        table.name, name
+                    ));
+                    return vec![];
+                }
+            };
+            vec![AlterAction::AlterColumn {
+                name: name.clone(),
+                action,
+            }]
+        }
+        AlterAction::AddConstraint { name, .. } => vec![AlterAction::drop_constraint(name.clone())],
+        AlterAction::ValidateConstraint { .. } => vec![],
+        AlterAction::DropConstraint { name } => {
+            let constraint = column_constraints(table)
+                .into_iter()
+                .chain(table.constraints.iter().cloned())
+                .find(|c| &c.name == name);
+            match constraint {
+                Some(constraint) => vec![AlterAction::add_table_constraint(constraint)],
+                None => {
+                    reasons.push(format!("the definition of {} is unknown", name));
+                    vec![]
+                }
+            }
+        }
+        AlterAction::AddPrimaryKey(pk) => vec![AlterAction::drop_primary_key(
+            pk.name
+                .clone()
+                .unwrap_or_else(|| format!("{}_pkey", table.name)),
+        )],
+        AlterAction::DropPrimaryKey { .. } => vec![AlterAction::AddPrimaryKey(PrimaryKey {
+            name: table.primary_key.as_ref().and_then(|pk| pk.name.clone()),
+            columns: table.primary_key_columns(),
+        })],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::migrate::{migrate, MigrationOptions};
+    use crate::schema::{Column, Index, Schema, Sequence, Table};
+    use crate::{Dialect, ToSql, Type};
+
+    #[test]
+    fn test_down() {
+        let column = |name: &str, typ: Type, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, typ)
+        };
+        let current = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("name", Type::Text, true))
+                    .column(column("age", Type::I64, true)),
+                Table::new("session")
+                    .comment("Logins")
+                    .column(Column::new("id", Type::I64).default("nextval('session_id_seq')"))
+                    .index(Index {
+                        name: "session_id_idx".to_string(),
+                        columns: vec!["id".to_string()],
+                    }),
+            ],
+            sequences: vec![Sequence::new("session_id_seq").owned_by("session", "id")],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("user")
+                    .column(column("name", Type::Text, false))
+                    .column(column("age", Type::I32, true))
+                    .column(column("team_id", Type::I32, true)),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            allow_destructive: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(current, desired, &options).unwrap();
+        let down = migration
+            .down
+            .iter()
+            .map(|s| s.to_sql(Dialect::Postgres))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            down,
+            vec![
+                r#"CREATE SEQUENCE "session_id_seq""#,
+                "CREATE TABLE \"session\" (\n\"id\" bigint DEFAULT nextval('session_id_seq')\n)",
+                r#"CREATE INDEX "session_id_idx" ON "session" USING BTREE ("id")"#,
+                r#"COMMENT ON TABLE "session" IS 'Logins'"#,
+                r#"ALTER SEQUENCE "session_id_seq" OWNED BY "session"."id""#,
+                concat!(
+                    r#"ALTER TABLE "user" DROP COLUMN "team_id","#,
+                    r#" ALTER COLUMN "age" TYPE bigint USING "age"::bigint,"#,
+                    r#" ALTER COLUMN "name" DROP NOT NULL"#,
+                ),
+                r#"DROP TABLE "team""#,
+            ]
+        );
+        let reasons = migration
+            .irreversible
+            .iter()
+            .map(|i| i.reason.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                "dropping session loses its rows",
+                "converting user.age from bigint to integer can lose data",
+            ]
+        );
+    }
+}
@@ -0,0 +1,436 @@
+use std::fmt;
+
+use super::online::not_null_check_name;
+use super::{Migration, Statement};
+use crate::query::{AlterAction, AlterColumnAction, AlterTable, Expr};
+use crate::schema::{Column, Constraint, Conversion, Generated};
+use crate::{Dialect, ToSql};
+use LockLevel::{
+    AccessExclusive, AccessShare, Exclusive, RowExclusive, Share, ShareRowExclusive,
+    ShareUpdateExclusive,
+};
+
+/// The strongest lock a statement takes on an existing table, following Postgres lock modes.
+/// Ordered from least to most blocking.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LockLevel {
+    /// No lock on an existing table, e.g. creating a schema or sequence.
+    None,
+    /// Blocks nothing but `ACCESS EXCLUSIVE`, e.g. `SELECT`.
+    AccessShare,
+    /// Blocks locks stronger than `SHARE UPDATE EXCLUSIVE`, e.g. `UPDATE`.
+    RowExclusive,
+    /// Blocks schema changes and vacuum, but not reads or writes, e.g. `CREATE INDEX
+    /// CONCURRENTLY`.
+    ShareUpdateExclusive,
+    /// Blocks writes, e.g. `CREATE INDEX`.
+    Share,
+    /// Blocks writes, e.g. adding a foreign key, on both tables.
+    ShareRowExclusive,
+    /// Blocks writes, and reads from other than `SELECT`, e.g. `REFRESH MATERIALIZED VIEW
+    /// CONCURRENTLY`.
+    Exclusive,
+    /// Blocks reads and writes, e.g. most `ALTER TABLE` actions.
+    AccessExclusive,
+}
+
+impl fmt::Display for LockLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LockLevel::None => "no lock",
+            LockLevel::AccessShare => "ACCESS SHARE",
+            LockLevel::RowExclusive => "ROW EXCLUSIVE",
+            LockLevel::ShareUpdateExclusive => "SHARE UPDATE EXCLUSIVE",
+            LockLevel::Share => "SHARE",
+            LockLevel::ShareRowExclusive => "SHARE ROW EXCLUSIVE",
+            LockLevel::Exclusive => "EXCLUSIVE",
+            LockLevel::AccessExclusive => "ACCESS EXCLUSIVE",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How safe a statement is to run against a busy database. Ordered from safest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Safety {
+    /// Any lock is held briefly.
+    Safe,
+    /// Holds a lock blocking writes for time proportional to the size of the table.
+    Caution,
+    /// Rewrites the table while blocking reads and writes, or loses data.
+    Dangerous,
+}
+
+impl fmt::Display for Safety {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Safety::Safe => "safe",
+            Safety::Caution => "caution",
+            Safety::Dangerous => "dangerous",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The estimated impact of running a statement. Estimates follow Postgres 11 and later.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementRisk {
+    pub lock: LockLevel,
+    /// Whether the table is rewritten, holding the lock for the duration.
+    pub rewrites_table: bool,
+    /// Whether every row is read, e.g. to validate a constraint, holding the lock for the
+    /// duration.
+    pub scans_table: bool,
+    pub safety: Safety,
+}
+
+impl StatementRisk {
+    fn new(lock: LockLevel, safety: Safety) -> Self {
+        StatementRisk {
+            lock,
+            rewrites_table: false,
+            scans_table: false,
+            safety,
+        }
+    }
+
+    fn scans(mut self) -> Self {
+        self.scans_table = true;
+        self
+    }
+
+    fn rewrites(mut self) -> Self {
+        self.rewrites_table = true;
+        self
+    }
+
+    /// The combined risk of running both, e.g. for the actions of one `ALTER TABLE`.
+    fn max(self, other: StatementRisk) -> Self {
+        StatementRisk {
+            lock: self.lock.max(other.lock),
+            rewrites_table: self.rewrites_table || other.rewrites_table,
+            scans_table: self.scans_table || other.scans_table,
+            safety: self.safety.max(other.safety),
+        }
+    }
+}
+
+/// Defaults that are evaluated for each row, which force a rewrite when adding a column.
+pub(super) fn is_volatile(expr: &Expr) -> bool {
+    let sql = expr.to_sql(Dialect::Postgres).to_lowercase();
+    ["random(", "clock_timestamp(", "gen_random_uuid(", "uuid_generate_", "nextval("]
+        .iter()
+        .any(|f| sql.contains(f))
+}
+
+fn add_column_risk(column: &Column) -> StatementRisk {
+    let mut risk = match &column.generated {
+        Some(Generated::Stored(_)) | Some(Generated::Identity { .. }) => {
+            StatementRisk::new(AccessExclusive, Safety::Dangerous).rewrites()
+        }
+        None if column.default.as_ref().is_some_and(is_volatile) => {
+            StatementRisk::new(AccessExclusive, Safety::Dangerous).rewrites()
+        }
+        None => StatementRisk::new(AccessExclusive, Safety::Safe),
+    };
+    if let Some(constraint) = &column.constraint {
+        risk = risk.max(constraint_risk(constraint));
+    }
+    risk
+}
+
+fn constraint_risk(constraint: &Constraint) -> StatementRisk {
+    match constraint {
+        Constraint::ForeignKey(_) => StatementRisk::new(ShareRowExclusive, Safety::Caution).scans(),
+        Constraint::Check(_) | Constraint::Unique | Constraint::Exclude(_) => {
+            StatementRisk::new(AccessExclusive, Safety::Caution).scans()
+        }
+    }
+}
+
+fn alter_risk(action: &AlterAction) -> StatementRisk {
+    use AlterColumnAction::*;
+    match action {
+        AlterAction::AddColumn { column } => add_column_risk(column),
+        AlterAction::DropColumn { .. } => StatementRisk::new(AccessExclusive, Safety::Dangerous),
+        AlterAction::RenameColumn { .. } => StatementRisk::new(AccessExclusive, Safety::Safe),
+        // MySQL copies the table, unless it can change the column in place
+        AlterAction::ModifyColumn { .. } => {
+            StatementRisk::new(AccessExclusive, Safety::Dangerous).rewrites()
+        }
+        AlterAction::AlterColumn { action, .. } => match action {
+            // the stored values are kept as they are
+            SetType {
+                conversion: Some(Conversion::BinaryCompatible),
+                ..
+            } => StatementRisk::new(AccessExclusive, Safety::Safe),
+            // without a known conversion, the type change is assumed to need a rewrite
+            SetType { .. } | SetExpression(_) => {
+                StatementRisk::new(AccessExclusive, Safety::Dangerous).rewrites()
+            }
+            SetNullable(false) => StatementRisk::new(AccessExclusive, Safety::Caution).scans(),
+            SetNullable(true)
+            | SetDefault(_)
+            | DropDefault
+            | AddIdentity { .. }
+            | SetIdentity { .. }
+            | DropIdentity
+            | DropExpression => StatementRisk::new(AccessExclusive, Safety::Safe),
+        },
+        // the existing rows are checked later by `VALIDATE CONSTRAINT`
+        AlterAction::AddConstraint {
+            constraint,
+            not_valid: true,
+            ..
+        } => StatementRisk::new(constraint_risk(constraint).lock, Safety::Safe),
+        AlterAction::AddConstraint { constraint, .. } => constraint_risk(constraint),
+        AlterAction::ValidateConstraint { .. } => {
+            StatementRisk::new(ShareUpdateExclusive, Safety::Safe).scans()
+        }
+        AlterAction::AddPrimaryKey(_) => {
+            StatementRisk::new(AccessExclusive, Safety::Caution).scans()
+        }
+        AlterAction::DropConstraint { .. } | AlterAction::DropPrimaryKey { .. } => {
+            StatementRisk::new(AccessExclusive, Safety::Safe)
+        }
+    }
+}
+
+fn combined(risks: impl Iterator<Item = StatementRisk>) -> StatementRisk {
+    risks.fold(StatementRisk::new(LockLevel::None, Safety::Safe), StatementRisk::max)
+}
+
+/// Whether other statements of the migration make `action`, of `statements[i]`, safe: setting a
+/// column `NOT NULL` once a check constraint proved it, or dropping a column replaced by a copy.
+fn is_covered(
+    statements: &[Statement],
+    i: usize,
+    alter: &AlterTable,
+    action: &AlterAction,
+) -> bool {
+    let any_action = |statements: &[Statement], f: &dyn Fn(&AlterAction) -> bool| {
+        statements.iter().any(|s| match s {
+            Statement::AlterTable(b) => {
+                b.schema == alter.schema && b.name == alter.name && b.actions.iter().any(f)
+            }
+            _ => false,
+        })
+    };
+    match action {
+        AlterAction::AlterColumn {
+            name: column,
+            action: AlterColumnAction::SetNullable(false),
+        } => {
+            let check = not_null_check_name(&alter.name, column);
+            any_action(&statements[..i], &|a| {
+                matches!(a, AlterAction::ValidateConstraint { name } if *name == check)
+            })
+        }
+        AlterAction::DropColumn { name } => any_action(&statements[i + 1..], &|a| {
+            matches!(a, AlterAction::RenameColumn { new_name, .. } if new_name == name)
+        }),
+        _ => false,
+    }
+}
+
+impl Statement {
+    /// The estimated lock, and whether the statement rewrites or scans an existing table.
+    pub fn risk(&self) -> StatementRisk {
+        match self {
+            Statement::CreateSchema(_)
+            | Statement::DropSchema(_)
+            | Statement::CreateEnum(_)
+            | Statement::AlterEnum(_)
+            | Statement::DropEnum(_)
+            | Statement::CreateSequence(_)
+            | Statement::AlterSequence(_)
+            | Statement::DropSequence(_)
+            | Statement::CreateView(_)
+            | Statement::DropView(_)
+            | Statement::Pragma(_) => StatementRisk::new(LockLevel::None, Safety::Safe),
+            // referenced tables are locked to add the foreign keys
+            Statement::CreateTable(c) => {
+                let references = c
+                    .columns
+                    .iter()
+                    .filter_map(|c| c.constraint.as_ref())
+                    .chain(c.constraints.iter().map(|c| &c.constraint))
+                    .any(|c| matches!(c, Constraint::ForeignKey(_)));
+                let lock = if references { ShareRowExclusive } else { LockLevel::None };
+                StatementRisk::new(lock, Safety::Safe)
+            }
+            Statement::DropTable(_) => StatementRisk::new(AccessExclusive, Safety::Dangerous),
+            Statement::RebuildTable(_) => {
+                StatementRisk::new(AccessExclusive, Safety::Dangerous).rewrites()
+            }
+            Statement::CreateIndex(c) if c.concurrently => {
+                StatementRisk::new(ShareUpdateExclusive, Safety::Safe).scans()
+            }
+            Statement::CreateIndex(_) => StatementRisk::new(Share, Safety::Caution).scans(),
+            Statement::DropIndex(_) => StatementRisk::new(AccessExclusive, Safety::Safe),
+            Statement::AlterTable(a) => combined(a.actions.iter().map(alter_risk)),
+            Statement::Update(_) => StatementRisk::new(RowExclusive, Safety::Caution).scans(),
+            // each batch commits, so rows are only locked briefly
+            Statement::Backfill(b) if b.is_batched() => {
+                StatementRisk::new(RowExclusive, Safety::Safe).scans()
+            }
+            Statement::Backfill(_) => StatementRisk::new(RowExclusive, Safety::Caution).scans(),
+            // held for the rest of the transaction, including the statements after it
+            Statement::LockTable(_) => StatementRisk::new(AccessExclusive, Safety::Caution),
+            Statement::CreateTrigger(_) => StatementRisk::new(ShareRowExclusive, Safety::Safe),
+            Statement::DropTrigger(_) => StatementRisk::new(AccessExclusive, Safety::Safe),
+            Statement::Select(_) => StatementRisk::new(AccessShare, Safety::Safe),
+            Statement::CommentOn(_) => StatementRisk::new(ShareUpdateExclusive, Safety::Safe),
+            Statement::RefreshMaterializedView(r) if r.concurrently => {
+                StatementRisk::new(Exclusive, Safety::Safe).scans()
+            }
+            Statement::RefreshMaterializedView(_) => {
+                StatementRisk::new(AccessExclusive, Safety::Caution).scans()
+            }
+        }
+    }
+}
+
+/// The risk of each statement of a migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskReport {
+    pub statements: Vec<(Statement, StatementRisk)>,
+}
+
+impl RiskReport {
+    /// The least safe rating of the statements.
+    pub fn safety(&self) -> Safety {
+        self.statements
+            .iter()
+            .map(|(_, risk)| risk.safety)
+            .max()
+            .unwrap_or(Safety::Safe)
+    }
+
+    /// The statements rated `safety` or worse.
+    pub fn at_least(&self, safety: Safety) -> impl Iterator<Item = &(Statement, StatementRisk)> {
+        self.statements.iter().filter(move |(_, risk)| risk.safety >= safety)
+    }
+}
+
+impl fmt::Display for RiskReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (statement, risk)) in self.statements.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "[{}] {}", risk.safety, risk.lock)?;
+            if risk.rewrites_table {
+                write!(f, ", rewrites table")?;
+            } else if risk.scans_table {
+                write!(f, ", scans table")?;
+            }
+            write!(f, ": {}", statement.to_sql(Dialect::Postgres))?;
+        }
+        Ok(())
+    }
+}
+
+impl Migration {
+    /// The estimated locks and safety of the statements, for deploys to gate on. Unlike
+    /// `Statement::risk`, this accounts for the steps of online migrations.
+    pub fn risk_report(&self) -> RiskReport {
+        let statements = self
+            .statements
+            .iter()
+            .enumerate()
+            .map(|(i, statement)| {
+                let risk = match statement {
+                    Statement::AlterTable(a) => combined(a.actions.iter().map(|action| {
+                        if is_covered(&self.statements, i, a, action) {
+                            StatementRisk::new(AccessExclusive, Safety::Safe)
+                        } else {
+                            alter_risk(action)
+                        }
+                    })),
+                    statement => statement.risk(),
+                };
+                (statement.clone(), risk)
+            })
+            .collect();
+        RiskReport { statements }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrate::{migrate, MigrationOptions};
+    use crate::schema::{ForeignKey, Schema, Table};
+    use crate::Type;
+
+    #[test]
+    fn test_risk_report() {
+        let column = |name: &str, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, Type::I32)
+        };
+        let current = Schema {
+            tables: vec![
+                Table::new("user").column(column("age", true)),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![
+                Table::new("user").column(column("age", false)).column(
+                    column("team_id", true)
+                        .constraint(Constraint::ForeignKey(ForeignKey::new("team", &["id"]))),
+                ),
+                Table::new("team"),
+            ],
+            ..Schema::default()
+        };
+        let migration = migrate(current, desired, &MigrationOptions::default()).unwrap();
+        let report = migration.risk_report();
+        assert_eq!(report.safety(), Safety::Caution);
+        assert_eq!(
+            report.statements[0].1,
+            StatementRisk {
+                lock: LockLevel::AccessExclusive,
+                rewrites_table: false,
+                scans_table: true,
+                safety: Safety::Caution,
+            }
+        );
+
+        let mut alter = Table::new("user").column(column("id", false));
+        alter.columns[0].default = Some(Expr::Raw("gen_random_uuid()".to_string()));
+        let add = crate::query::AlterTable {
+            schema: None,
+            name: "user".to_string(),
+            actions: vec![AlterAction::AddColumn {
+                column: alter.columns[0].clone(),
+            }],
+            enums: vec![],
+        };
+        let risk = Statement::AlterTable(add).risk();
+        assert!(risk.rewrites_table);
+        assert_eq!(risk.safety, Safety::Dangerous);
+
+        // binary compatible types don't rewrite the table, so they're changed in place even online
+        let schema = |typ: Type| Schema {
+            tables: vec![Table::new("user").column(Column::new("name", typ))],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            online: true,
+            ..MigrationOptions::default()
+        };
+        let migration = migrate(schema(Type::Varchar(64)), schema(Type::Text), &options).unwrap();
+        assert_eq!(migration.statements.len(), 1);
+        let risk = migration.statements[0].risk();
+        assert!(!risk.rewrites_table);
+        assert_eq!(risk.safety, Safety::Safe);
+        let migration = migrate(schema(Type::Varchar(64)), schema(Type::I32), &options).unwrap();
+        assert!(migration.statements.len() > 1);
+        let migration = migrate(schema(Type::I32), schema(Type::I64), &Default::default()).unwrap();
+        assert!(migration.statements[0].risk().rewrites_table);
+    }
+}
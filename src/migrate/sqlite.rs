@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use super::{online, table_key, Statement};
+use crate::query::{AlterAction, CreateIndex, CreateTable, Expr, Pragma, RebuildTable};
+use crate::schema::{Constraint, Generated, Schema, Table, View};
+
+type Key = (String, String);
+
+fn find<'a>(schema: &'a Schema, key: &Key, default_schema: &str) -> Option<&'a Table> {
+    schema
+        .tables
+        .iter()
+        .find(|t| table_key(&t.schema, &t.name, default_schema) == (&key.0, &key.1))
+}
+
+/// Whether SQLite can make the change with `ALTER TABLE`, which only adds, drops and renames
+/// columns.
+fn is_supported(action: &AlterAction, current: &Table, views: &[View]) -> bool {
+    match action {
+        // a new column can't be a key, and needs a value for the existing rows
+        AlterAction::AddColumn { column } => {
+            !column.primary_key
+                && !matches!(column.generated, Some(Generated::Stored(_)))
+                && (column.nullable || column.default.is_some())
+                && match &column.constraint {
+                    Some(Constraint::Unique) => false,
+                    // with foreign keys on, a referencing column has to default to null
+                    Some(Constraint::ForeignKey(_)) => column.default.is_none(),
+                    _ => true,
+                }
+        }
+        AlterAction::DropColumn { name } => online::is_unreferenced(current, name, views),
+        AlterAction::RenameColumn { .. } => true,
+        _ => false,
+    }
+}
+
+/// Replace the `ALTER TABLE`s SQLite can't run with rebuilds of their tables. The backfills and
+/// new indexes of a rebuilt table become part of its rebuild, and foreign keys are turned off
+/// while the rebuilds run.
+pub(super) fn sqlite_statements(
+    statements: Vec<Statement>,
+    current: &Schema,
+    desired: &Schema,
+    default_schema: &str,
+) -> Vec<Statement> {
+    let key_of = |statement: &Statement| {
+        let (schema, name) = match statement {
+            Statement::AlterTable(a) => (&a.schema, &a.name),
+            Statement::Backfill(b) => (&b.schema, &b.table),
+            Statement::Update(u) => (&u.schema, &u.table),
+            Statement::CreateIndex(c) => (&c.schema, &c.table),
+            _ => return None,
+        };
+        let (schema, name) = table_key(schema, name, default_schema);
+        Some((schema.to_string(), name.to_string()))
+    };
+    let mut rebuilt = vec![];
+    // the values of new columns, and the columns added, dropped or renamed, by table
+    let mut values = HashMap::new();
+    let mut added = HashMap::<Key, Vec<String>>::new();
+    let mut dropped = HashMap::<Key, Vec<String>>::new();
+    for statement in &statements {
+        let Some(key) = key_of(statement) else {
+            continue;
+        };
+        match statement {
+            Statement::AlterTable(a) => {
+                let (Some(table), Some(_)) = (
+                    find(current, &key, default_schema),
+                    find(desired, &key, default_schema),
+                ) else {
+                    continue;
+                };
+                for action in &a.actions {
+                    match action {
+                        AlterAction::AddColumn { column } => {
+                            added.entry(key.clone()).or_default().push(column.name.clone());
+                        }
+                        AlterAction::DropColumn { name } => {
+                            dropped.entry(key.clone()).or_default().push(name.clone());
+                        }
+                        AlterAction::RenameColumn { name, new_name } => {
+                            dropped.entry(key.clone()).or_default().push(name.clone());
+                            values.insert((key.clone(), new_name.clone()), Expr::column(name));
+                        }
+                        _ => {}
+                    }
+                }
+                if !a.actions.iter().all(|action| is_supported(action, table, &current.views))
+                    && !rebuilt.contains(&key)
+                {
+                    rebuilt.push(key);
+                }
+            }
+            Statement::Backfill(b) => {
+                values.insert((key, b.column.clone()), b.value.clone());
+            }
+            _ => {}
+        }
+    }
+    if rebuilt.is_empty() {
+        return statements;
+    }
+
+    let mut planned = vec![];
+    let mut done = vec![];
+    for statement in statements {
+        let Some(key) = key_of(&statement).filter(|key| rebuilt.contains(key)) else {
+            planned.push(statement);
+            continue;
+        };
+        if done.contains(&key) {
+            continue;
+        }
+        let (Some(current_table), Some(desired_table)) = (
+            find(current, &key, default_schema),
+            find(desired, &key, default_schema),
+        ) else {
+            continue;
+        };
+        let value = |column: &str| values.get(&(key.clone(), column.to_string())).cloned();
+        let changes = |columns: &HashMap<Key, Vec<String>>| {
+            columns.get(&key).cloned().unwrap_or_default()
+        };
+        let mut rebuild = rebuild_table(
+            current_table,
+            desired_table,
+            (&changes(&added), &changes(&dropped)),
+            value,
+        );
+        rebuild.table.enums = desired.enums_for(desired_table);
+        planned.push(Statement::RebuildTable(rebuild));
+        done.push(key);
+    }
+    let is_rebuild = |s: &Statement| matches!(s, Statement::RebuildTable(_));
+    if let (Some(first), Some(last)) = (
+        planned.iter().position(is_rebuild),
+        planned.iter().rposition(is_rebuild),
+    ) {
+        planned.insert(last + 1, Statement::Pragma(Pragma::new("foreign_keys").value("on")));
+        planned.insert(first, Statement::Pragma(Pragma::new("foreign_keys").value("off")));
+    }
+    planned
+}
+
+/// Rebuild `current` into `desired`. Like `ALTER TABLE`, it keeps the columns missing from
+/// `desired` that aren't dropped, along with their constraints and indexes. The other columns
+/// are copied, except the added ones, which are filled by `value` or get their default.
+fn rebuild_table(
+    current: &Table,
+    desired: &Table,
+    (added, dropped): (&[String], &[String]),
+    value: impl Fn(&str) -> Option<Expr>,
+) -> RebuildTable {
+    let mut table = desired.clone();
+    let has_column = |t: &Table, name: &str| t.columns.iter().any(|c| c.name == name);
+    for column in &current.columns {
+        if !has_column(desired, &column.name) && !dropped.contains(&column.name) {
+            table.columns.push(column.clone());
+        }
+    }
+    let kept = |columns: &[String], t: &Table| {
+        !columns.iter().all(|c| has_column(desired, c))
+            && columns.iter().all(|c| has_column(t, c))
+    };
+    for constraint in &current.constraints {
+        if kept(&constraint.columns, &table) {
+            table.constraints.push(constraint.clone());
+        }
+    }
+    for index in &current.indexes {
+        if !desired.indexes.iter().any(|i| i.name == index.name) && kept(&index.columns, &table) {
+            table.indexes.push(index.clone());
+        }
+    }
+    let columns = table
+        .columns
+        .iter()
+        .filter(|c| !matches!(c.generated, Some(Generated::Stored(_))))
+        .filter_map(|c| {
+            let value = match value(&c.name) {
+                Some(value) => value,
+                None if added.contains(&c.name) => return None,
+                None => Expr::column(&c.name),
+            };
+            Some((c.name.clone(), value))
+        })
+        .collect();
+    RebuildTable {
+        table: CreateTable::from_table(&table),
+        columns,
+        indexes: table
+            .indexes
+            .iter()
+            .map(|i| CreateIndex::from_index(&table, i))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::migrate::{migrate, MigrationOptions};
+    use crate::schema::{Column, Index, Schema, Table, Type};
+    use crate::{Dialect, Expr, ToSql};
+
+    #[test]
+    fn test_rebuild_table() {
+        let column = |name: &str, typ: Type, nullable: bool| Column {
+            nullable,
+            ..Column::new(name, typ)
+        };
+        let id = Column::new("id", Type::I64).primary_key();
+        let current = Schema {
+            tables: vec![Table::new("user")
+                .column(id.clone())
+                .column(column("age", Type::I32, true))
+                .column(column("name", Type::Text, true))],
+            ..Schema::default()
+        };
+        let desired = Schema {
+            tables: vec![Table::new("user")
+                .column(id)
+                .column(column("age", Type::F64, false))
+                .column(column("score", Type::I32, false))
+                .index(Index {
+                    name: "user_score_idx".to_string(),
+                    columns: vec!["score".to_string()],
+                })],
+            ..Schema::default()
+        };
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Sqlite),
+            ..MigrationOptions::default()
+        }
+        .backfill("user", "score", Expr::Raw(r#""id" * 10"#.to_string()));
+        let migration = migrate(current.clone(), desired.clone(), &options).unwrap();
+        let sql = migration
+            .statements
+            .iter()
+            .map(|s| s.to_sql(Dialect::Sqlite))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sql,
+            vec![
+                "PRAGMA foreign_keys = off",
+                r#"CREATE TABLE "user__sqlmo_new" (
+"id" INTEGER NOT NULL PRIMARY KEY,
+"age" DOUBLE NOT NULL,
+"score" INT NOT NULL,
+"name" TEXT
+);
+INSERT INTO "user__sqlmo_new" ("id", "age", "score", "name") SELECT "id", "age", "id" * 10, "name" FROM "user";
+DROP TABLE "user";
+ALTER TABLE "user__sqlmo_new" RENAME TO "user";
+CREATE INDEX "user_score_idx" ON "user" ("score");
+CREATE TEMP TABLE "sqlmo_foreign_key_check" ("violations" INTEGER CHECK ("violations" = 0));
+INSERT INTO "sqlmo_foreign_key_check" SELECT count(*) FROM pragma_foreign_key_check();
+DROP TABLE "sqlmo_foreign_key_check""#,
+                "PRAGMA foreign_keys = on",
+            ]
+        );
+        assert!(!migration.statements[0].is_transactional());
+        assert!(migration.statements[1].is_transactional());
+        // the down migration rebuilds the table as it was, keeping the column `desired` left out
+        assert_eq!(
+            migration.down[2].to_sql(Dialect::Sqlite),
+            r#"CREATE TABLE "user__sqlmo_new" (
+"id" INTEGER NOT NULL PRIMARY KEY,
+"age" INT,
+"name" TEXT
+);
+INSERT INTO "user__sqlmo_new" ("id", "age", "name") SELECT "id", "age", "name" FROM "user";
+DROP TABLE "user";
+ALTER TABLE "user__sqlmo_new" RENAME TO "user";
+CREATE TEMP TABLE "sqlmo_foreign_key_check" ("violations" INTEGER CHECK ("violations" = 0));
+INSERT INTO "sqlmo_foreign_key_check" SELECT count(*) FROM pragma_foreign_key_check();
+DROP TABLE "sqlmo_foreign_key_check""#
+        );
+
+        // columns SQLite can add are added in place
+        let options = MigrationOptions {
+            dialect: Some(Dialect::Sqlite),
+            ..MigrationOptions::default()
+        };
+        let mut added = current.clone();
+        added.tables[0].columns.push(column("email", Type::Text, true));
+        let migration = migrate(current, added, &options).unwrap();
+        assert_eq!(
+            migration.statements[0].to_sql(Dialect::Sqlite),
+            r#"ALTER TABLE "user" ADD COLUMN "email" TEXT"#
+        );
+    }
+}
@@ -1,11 +1,29 @@
+mod alter_enum;
+mod alter_sequence;
 mod alter_table;
+mod backfill;
+mod comment;
+mod create_enum;
 mod create_index;
 mod create_schema;
+mod create_sequence;
 mod create_table;
+mod create_trigger;
+mod create_view;
 mod cte;
 mod delete;
+mod drop_enum;
+mod drop_index;
+mod drop_schema;
+mod drop_sequence;
 mod drop_table;
+mod drop_trigger;
+mod drop_view;
 mod insert;
+mod lock_table;
+mod pragma;
+mod rebuild_table;
+mod refresh_view;
 mod select;
 mod update;
 mod union;
@@ -14,10 +32,28 @@ pub use insert::*;
 pub use select::*;
 pub use update::*;
 // pub use delete::*;
+pub use alter_enum::*;
+pub use alter_sequence::*;
 pub use alter_table::*;
+pub use backfill::*;
+pub use comment::*;
+pub use create_enum::*;
 pub use create_index::*;
 pub use create_schema::*;
+pub use create_sequence::*;
 pub use create_table::*;
+pub use create_trigger::*;
+pub use create_view::*;
 pub use cte::*;
+pub use drop_enum::*;
+pub use drop_index::*;
+pub use drop_schema::*;
+pub use drop_sequence::*;
 pub use drop_table::*;
+pub use drop_trigger::*;
+pub use drop_view::*;
+pub use lock_table::*;
+pub use pragma::*;
+pub use rebuild_table::*;
+pub use refresh_view::*;
 pub use union::*;
@@ -0,0 +1,44 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Where to place a new enum value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnumValuePosition {
+    Before(String),
+    After(String),
+}
+
+/// Add a value to an enum type. Postgres can't remove values from an enum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterEnum {
+    pub schema: Option<String>,
+    pub name: String,
+    pub value: String,
+    pub position: Option<EnumValuePosition>,
+}
+
+impl ToSql for AlterEnum {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        if dialect != Dialect::Postgres {
+            buf.push_str("/* enum ");
+            buf.push_str(&self.name);
+            buf.push_str(" is declared inline */");
+            return;
+        }
+        buf.push_str("ALTER TYPE ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str(" ADD VALUE ");
+        buf.push_literal(&self.value, dialect);
+        match &self.position {
+            Some(EnumValuePosition::Before(v)) => {
+                buf.push_str(" BEFORE ");
+                buf.push_literal(v, dialect);
+            }
+            Some(EnumValuePosition::After(v)) => {
+                buf.push_str(" AFTER ");
+                buf.push_literal(v, dialect);
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::schema::SequenceOptions;
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Change the options of a sequence. Only the options that are set are changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlterSequence {
+    pub schema: Option<String>,
+    pub name: String,
+    pub options: SequenceOptions,
+}
+
+impl ToSql for AlterSequence {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("ALTER SEQUENCE ");
+        buf.push_table_name(&self.schema, &self.name);
+        self.options.write_sql(buf, dialect, &self.schema);
+    }
+}
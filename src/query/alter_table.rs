@@ -1,11 +1,30 @@
-use crate::schema::Constraint;
+use crate::query::Expr;
+use crate::schema::{Constraint, Conversion, Enum, PrimaryKey, TableConstraint};
 use crate::util::SqlExtension;
 use crate::{Column, Dialect, ToSql, Type};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AlterColumnAction {
-    SetType { typ: Type, using: Option<String> },
+    SetType {
+        typ: Type,
+        /// Converts the existing values. Defaults to a cast.
+        using: Option<Expr>,
+        /// How the existing values convert, if known. Otherwise the table is assumed to be
+        /// rewritten.
+        conversion: Option<Conversion>,
+    },
     SetNullable(bool),
+    SetDefault(Expr),
+    DropDefault,
+    /// `ADD GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY`
+    AddIdentity { always: bool },
+    /// `SET GENERATED { ALWAYS | BY DEFAULT }`
+    SetIdentity { always: bool },
+    DropIdentity,
+    /// `SET EXPRESSION AS (expr)` for a generated column. Requires Postgres 17.
+    SetExpression(Expr),
+    /// Turn a generated column into a regular one.
+    DropExpression,
 }
 
 /// Alter table action
@@ -14,14 +33,39 @@ pub enum AlterAction {
     AddColumn {
         column: Column,
     },
+    DropColumn {
+        name: String,
+    },
+    RenameColumn {
+        name: String,
+        new_name: String,
+    },
     AlterColumn {
         name: String,
         action: AlterColumnAction,
     },
+    /// MySQL's `MODIFY COLUMN`, which changes a column's type or nullability by redefining the
+    /// whole column.
+    ModifyColumn {
+        column: Column,
+    },
     AddConstraint {
         name: String,
-        column: String,
+        columns: Vec<String>,
         constraint: Constraint,
+        /// Skip checking the existing rows, which `ValidateConstraint` does later without
+        /// blocking writes. Postgres only, for foreign keys and checks.
+        not_valid: bool,
+    },
+    ValidateConstraint {
+        name: String,
+    },
+    DropConstraint {
+        name: String,
+    },
+    AddPrimaryKey(PrimaryKey),
+    DropPrimaryKey {
+        name: String,
     },
 }
 
@@ -36,18 +80,61 @@ impl AlterAction {
     pub fn set_type(name: String, typ: Type) -> Self {
         Self::AlterColumn {
             name,
-            action: AlterColumnAction::SetType { typ, using: None },
+            action: AlterColumnAction::SetType {
+                typ,
+                using: None,
+                conversion: None,
+            },
+        }
+    }
+
+    pub fn set_default(name: String, default: Expr) -> Self {
+        Self::AlterColumn {
+            name,
+            action: AlterColumnAction::SetDefault(default),
+        }
+    }
+
+    pub fn drop_default(name: String) -> Self {
+        Self::AlterColumn {
+            name,
+            action: AlterColumnAction::DropDefault,
         }
     }
 
+    pub fn drop_primary_key(name: String) -> Self {
+        Self::DropPrimaryKey { name }
+    }
+
     pub fn add_constraint(table: &str, column: String, constraint: Constraint) -> Self {
-        let name = format!("fk_{table}_{column}");
         Self::AddConstraint {
-            name,
-            column,
+            name: constraint.default_name(table, &column),
+            columns: vec![column],
             constraint,
+            not_valid: false,
+        }
+    }
+
+    pub fn add_table_constraint(constraint: TableConstraint) -> Self {
+        Self::AddConstraint {
+            name: constraint.name,
+            columns: constraint.columns,
+            constraint: constraint.constraint,
+            not_valid: false,
         }
     }
+
+    pub fn validate_constraint(name: String) -> Self {
+        Self::ValidateConstraint { name }
+    }
+
+    pub fn drop_constraint(name: String) -> Self {
+        Self::DropConstraint { name }
+    }
+
+    pub fn rename_column(name: String, new_name: String) -> Self {
+        Self::RenameColumn { name, new_name }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -55,6 +142,8 @@ pub struct AlterTable {
     pub schema: Option<String>,
     pub name: String,
     pub actions: Vec<AlterAction>,
+    /// Definitions of the enum types used by the actions, for dialects that declare them inline.
+    pub enums: Vec<Enum>,
 }
 
 impl ToSql for AlterTable {
@@ -71,33 +160,52 @@ impl ToSql for AlterTable {
         );
         buf.push_str("ALTER TABLE ");
         buf.push_table_name(&self.schema, &self.name);
-        buf.push_sql_sequence(&self.actions, ",", dialect);
+        for (i, action) in self.actions.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            action.write_sql_with_enums(buf, dialect, &self.enums);
+        }
     }
 }
 
-impl ToSql for AlterAction {
-    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+impl AlterAction {
+    fn write_sql_with_enums(&self, buf: &mut String, dialect: Dialect, enums: &[Enum]) {
         use AlterAction::*;
         match self {
             AddColumn { column } => {
                 buf.push_str(" ADD COLUMN ");
-                buf.push_str(&column.to_sql(dialect));
+                column.write_sql_with_enums(buf, dialect, enums);
+            }
+            DropColumn { name } => {
+                buf.push_str(" DROP COLUMN ");
+                buf.push_quoted(name);
+            }
+            RenameColumn { name, new_name } => {
+                buf.push_str(" RENAME COLUMN ");
+                buf.push_quoted(name);
+                buf.push_str(" TO ");
+                buf.push_quoted(new_name);
+            }
+            ModifyColumn { column } => {
+                buf.push_str(" MODIFY COLUMN ");
+                column.write_sql_with_enums(buf, dialect, enums);
             }
             AlterColumn { name, action } => {
                 use AlterColumnAction::*;
                 buf.push_str(" ALTER COLUMN ");
                 buf.push_quoted(name);
                 match action {
-                    SetType { typ, using } => {
+                    SetType { typ, using, .. } => {
                         buf.push_str(" TYPE ");
-                        buf.push_sql(typ, dialect);
+                        typ.write_sql_with_enums(buf, dialect, enums);
                         buf.push_str(" USING ");
                         if let Some(using) = using {
-                            buf.push_str(&using)
+                            buf.push_sql(using, dialect);
                         } else {
                             buf.push_quoted(name);
                             buf.push_str("::");
-                            buf.push_sql(typ, dialect);
+                            typ.write_sql_with_enums(buf, dialect, enums);
                         }
                     }
                     SetNullable(nullable) => {
@@ -107,24 +215,79 @@ impl ToSql for AlterAction {
                             buf.push_str(" SET NOT NULL");
                         }
                     }
+                    SetDefault(default) => {
+                        buf.push_str(" SET DEFAULT ");
+                        buf.push_sql(default, dialect);
+                    }
+                    DropDefault => {
+                        buf.push_str(" DROP DEFAULT");
+                    }
+                    AddIdentity { always } => {
+                        buf.push_str(" ADD GENERATED ");
+                        buf.push_str(if *always { "ALWAYS" } else { "BY DEFAULT" });
+                        buf.push_str(" AS IDENTITY");
+                    }
+                    SetIdentity { always } => {
+                        buf.push_str(" SET GENERATED ");
+                        buf.push_str(if *always { "ALWAYS" } else { "BY DEFAULT" });
+                    }
+                    DropIdentity => {
+                        buf.push_str(" DROP IDENTITY");
+                    }
+                    SetExpression(expr) => {
+                        buf.push_str(" SET EXPRESSION AS (");
+                        buf.push_sql(expr, dialect);
+                        buf.push(')');
+                    }
+                    DropExpression => {
+                        buf.push_str(" DROP EXPRESSION");
+                    }
                 }
             }
             AddConstraint {
                 name,
-                column,
+                columns,
                 constraint,
+                not_valid,
             } => {
                 buf.push_str(" ADD CONSTRAINT ");
                 buf.push_quoted(name);
-                buf.push_str(" FOREIGN KEY (");
-                buf.push_quoted(column);
-                buf.push_str(") ");
-                buf.push_sql(constraint, dialect);
+                buf.push(' ');
+                constraint.write_table_sql(columns, buf, dialect);
+                if *not_valid && dialect == Dialect::Postgres {
+                    buf.push_str(" NOT VALID");
+                }
+            }
+            ValidateConstraint { name } => {
+                buf.push_str(" VALIDATE CONSTRAINT ");
+                buf.push_quoted(name);
+            }
+            DropConstraint { name } => {
+                buf.push_str(" DROP CONSTRAINT ");
+                buf.push_quoted(name);
+            }
+            AddPrimaryKey(primary_key) => {
+                buf.push_str(" ADD ");
+                buf.push_sql(primary_key, dialect);
+            }
+            DropPrimaryKey { name } => {
+                if dialect == Dialect::Mysql {
+                    buf.push_str(" DROP PRIMARY KEY");
+                } else {
+                    buf.push_str(" DROP CONSTRAINT ");
+                    buf.push_quoted(name);
+                }
             }
         }
     }
 }
 
+impl ToSql for AlterAction {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        self.write_sql_with_enums(buf, dialect, &[]);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -136,6 +299,7 @@ mod test {
             action: AlterColumnAction::SetType {
                 typ: Type::Text,
                 using: None,
+                conversion: None,
             },
         };
         assert_eq!(
@@ -147,7 +311,8 @@ mod test {
             name: "foo".to_string(),
             action: AlterColumnAction::SetType {
                 typ: Type::Text,
-                using: Some("SUBSTRING(foo, 1, 3)".to_string()),
+                using: Some(Expr::Raw("SUBSTRING(foo, 1, 3)".to_string())),
+                conversion: None,
             },
         };
         assert_eq!(
@@ -155,4 +320,50 @@ mod test {
             r#" ALTER COLUMN "foo" TYPE character varying USING SUBSTRING(foo, 1, 3)"#
         );
     }
+
+    #[test]
+    fn test_modify_column() {
+        let alter = AlterAction::ModifyColumn {
+            column: Column::new("name", Type::Varchar(64)).not_null(),
+        };
+        assert_eq!(alter.to_sql(Dialect::Mysql), r#" MODIFY COLUMN "name" VARCHAR(64) NOT NULL"#);
+    }
+
+    #[test]
+    fn test_default() {
+        let alter = AlterAction::set_default("created_at".to_string(), "clock_timestamp()".into());
+        assert_eq!(
+            alter.to_sql(Dialect::Postgres),
+            r#" ALTER COLUMN "created_at" SET DEFAULT clock_timestamp()"#
+        );
+
+        let alter = AlterAction::drop_default("created_at".to_string());
+        assert_eq!(
+            alter.to_sql(Dialect::Postgres),
+            r#" ALTER COLUMN "created_at" DROP DEFAULT"#
+        );
+    }
+
+    #[test]
+    fn test_not_valid() {
+        let alter = AlterAction::AddConstraint {
+            name: "user_age_check".to_string(),
+            columns: vec!["age".to_string()],
+            constraint: Constraint::check("age >= 0"),
+            not_valid: true,
+        };
+        assert_eq!(
+            alter.to_sql(Dialect::Postgres),
+            r#" ADD CONSTRAINT "user_age_check" CHECK (age >= 0) NOT VALID"#
+        );
+        assert_eq!(
+            alter.to_sql(Dialect::Mysql),
+            r#" ADD CONSTRAINT "user_age_check" CHECK (age >= 0)"#
+        );
+        let alter = AlterAction::validate_constraint("user_age_check".to_string());
+        assert_eq!(
+            alter.to_sql(Dialect::Postgres),
+            r#" VALIDATE CONSTRAINT "user_age_check""#
+        );
+    }
 }
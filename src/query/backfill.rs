@@ -0,0 +1,127 @@
+use crate::query::{Expr, Select, Update, Where};
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Fill the nulls of a column with a value, e.g. before it's set `NOT NULL`. Renders as a single
+/// `UPDATE`. A batched backfill can instead be run one range of `key` at a time, each in its own
+/// transaction, so large tables aren't locked row by row for the whole update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backfill {
+    pub schema: Option<String>,
+    pub table: String,
+    pub column: String,
+    pub value: Expr,
+    /// An integer primary key column to batch the update by.
+    pub key: Option<String>,
+    /// The number of keys updated per batch.
+    pub batch_size: u64,
+}
+
+impl Backfill {
+    pub fn new(table: &str, column: &str, value: impl Into<Expr>) -> Self {
+        Backfill {
+            schema: None,
+            table: table.to_string(),
+            column: column.to_string(),
+            value: value.into(),
+            key: None,
+            batch_size: 0,
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn batched(mut self, key: &str, batch_size: u64) -> Self {
+        self.key = Some(key.to_string());
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn is_batched(&self) -> bool {
+        self.key.is_some() && self.batch_size > 0
+    }
+
+    /// Update all the rows at once.
+    pub fn update(&self, dialect: Dialect) -> Update {
+        let mut update = Update::new(&self.table).set(&self.column, &self.value.to_sql(dialect));
+        update.schema = self.schema.clone();
+        let mut is_null = String::new();
+        is_null.push_quoted(&self.column);
+        is_null.push_str(" IS NULL");
+        update.where_(Where::raw(is_null))
+    }
+
+    /// The smallest and largest key, as 64-bit integers, to split into batches.
+    pub fn bounds(&self, dialect: Dialect) -> Option<Select> {
+        let key = self.key.as_ref()?;
+        let bigint = match dialect {
+            Dialect::Mysql => "SIGNED",
+            Dialect::Postgres | Dialect::Sqlite => "BIGINT",
+        };
+        let mut select = Select::default();
+        for f in ["min", "max"] {
+            let mut bound = format!("CAST({f}(");
+            bound.push_quoted(key);
+            bound.push_str(&format!(") AS {bigint})"));
+            select = select.select_raw(bound);
+        }
+        let mut select = select.from(&self.table);
+        if let Some(from) = &mut select.from {
+            from.schema = self.schema.clone();
+        }
+        Some(select)
+    }
+
+    /// Update the rows with keys from the first placeholder, inclusive, to the second, exclusive.
+    pub fn batch(&self, dialect: Dialect) -> Option<Update> {
+        let key = self.key.as_ref()?;
+        let (start, end) = match dialect {
+            Dialect::Postgres => ("$1", "$2"),
+            Dialect::Mysql | Dialect::Sqlite => ("?", "?"),
+        };
+        let mut range = String::new();
+        range.push_quoted(key);
+        range.push_str(" >= ");
+        range.push_str(start);
+        range.push_str(" AND ");
+        range.push_quoted(key);
+        range.push_str(" < ");
+        range.push_str(end);
+        Some(self.update(dialect).where_(Where::raw(range)))
+    }
+}
+
+impl ToSql for Backfill {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_sql(&self.update(dialect), dialect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backfill() {
+        let backfill = Backfill::new("user", "status", Expr::Raw("'active'".to_string()))
+            .batched("id", 1000);
+        assert_eq!(
+            backfill.to_sql(Dialect::Postgres),
+            r#"UPDATE "user" SET "status" = 'active' WHERE "status" IS NULL"#
+        );
+        assert_eq!(
+            backfill.batch(Dialect::Postgres).unwrap().to_sql(Dialect::Postgres),
+            concat!(
+                r#"UPDATE "user" SET "status" = 'active' WHERE "status" IS NULL"#,
+                r#" AND "id" >= $1 AND "id" < $2"#,
+            )
+        );
+        assert_eq!(
+            backfill.bounds(Dialect::Postgres).unwrap().to_sql(Dialect::Postgres),
+            r#"SELECT CAST(min("id") AS BIGINT), CAST(max("id") AS BIGINT) FROM "user" "#
+        );
+    }
+}
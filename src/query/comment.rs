@@ -0,0 +1,150 @@
+use crate::schema::Enum;
+use crate::util::SqlExtension;
+use crate::{Column, Dialect, ToSql};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum CommentTarget {
+    Table,
+    /// MySQL comments a column by redefining it, so this holds the full column definition.
+    Column(Column),
+}
+
+/// Set or remove (with `None`) the comment on a table or column. Rendered as `COMMENT ON` in
+/// Postgres, and as an `ALTER TABLE` in MySQL. Sqlite doesn't support comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentOn {
+    pub schema: Option<String>,
+    pub table: String,
+    pub target: CommentTarget,
+    pub comment: Option<String>,
+    /// Definitions of the enum types used by the column, for dialects that declare them inline.
+    pub enums: Vec<Enum>,
+}
+
+impl CommentOn {
+    pub fn table(table: &str, comment: Option<String>) -> Self {
+        Self {
+            schema: None,
+            table: table.to_string(),
+            target: CommentTarget::Table,
+            comment,
+            enums: vec![],
+        }
+    }
+
+    pub fn column(table: &str, column: Column) -> Self {
+        Self {
+            schema: None,
+            table: table.to_string(),
+            comment: column.comment.clone(),
+            target: CommentTarget::Column(column),
+            enums: vec![],
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn enums(mut self, enums: Vec<Enum>) -> Self {
+        self.enums = enums;
+        self
+    }
+}
+
+impl ToSql for CommentOn {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        match (dialect, &self.target) {
+            (Dialect::Postgres, target) => {
+                buf.push_str("COMMENT ON ");
+                match target {
+                    CommentTarget::Table => {
+                        buf.push_str("TABLE ");
+                        buf.push_table_name(&self.schema, &self.table);
+                    }
+                    CommentTarget::Column(column) => {
+                        buf.push_str("COLUMN ");
+                        buf.push_table_name(&self.schema, &self.table);
+                        buf.push('.');
+                        buf.push_quoted(&column.name);
+                    }
+                }
+                buf.push_str(" IS ");
+                match &self.comment {
+                    Some(comment) => buf.push_literal(comment, dialect),
+                    None => buf.push_str("NULL"),
+                }
+            }
+            (Dialect::Mysql, target) => {
+                buf.push_str("ALTER TABLE ");
+                buf.push_table_name(&self.schema, &self.table);
+                match target {
+                    CommentTarget::Table => {
+                        buf.push_str(" COMMENT = ");
+                        buf.push_literal(self.comment.as_deref().unwrap_or(""), dialect);
+                    }
+                    CommentTarget::Column(column) => {
+                        let column = Column {
+                            comment: self.comment.clone(),
+                            ..column.clone()
+                        };
+                        buf.push_str(" MODIFY COLUMN ");
+                        column.write_sql_with_enums(buf, dialect, &self.enums);
+                    }
+                }
+            }
+            (Dialect::Sqlite, _) => {
+                buf.push_str("/* Sqlite doesn't support comments */");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Enum;
+    use crate::Type;
+
+    #[test]
+    fn test_comment_on() {
+        let c = CommentOn::table("user", Some("People who can log in".to_string()));
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"COMMENT ON TABLE "user" IS 'People who can log in'"#
+        );
+        assert_eq!(
+            c.to_sql(Dialect::Mysql),
+            r#"ALTER TABLE "user" COMMENT = 'People who can log in'"#
+        );
+
+        let column = Column::new("email", Type::Text)
+            .not_null()
+            .comment("Login, can't be changed");
+        let c = CommentOn::column("user", column).schema(Some("auth".to_string()));
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"COMMENT ON COLUMN "auth"."user"."email" IS 'Login, can''t be changed'"#
+        );
+        assert_eq!(
+            c.to_sql(Dialect::Mysql),
+            r#"ALTER TABLE "auth"."user" MODIFY COLUMN "email" TEXT NOT NULL COMMENT 'Login, can''t be changed'"#
+        );
+
+        let column = Column::new("mood", Type::Enum("mood".to_string())).comment(r"See C:\moods");
+        let c = CommentOn::column("user", column).enums(vec![Enum::new("mood", &["happy", "sad"])]);
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"COMMENT ON COLUMN "user"."mood" IS 'See C:\moods'"#
+        );
+        assert_eq!(
+            c.to_sql(Dialect::Mysql),
+            concat!(
+                r#"ALTER TABLE "user" MODIFY COLUMN "mood" ENUM('happy', 'sad') "#,
+                r"COMMENT 'See C:\\moods'",
+            )
+        );
+    }
+}
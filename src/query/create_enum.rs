@@ -0,0 +1,52 @@
+use crate::schema::Enum;
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Create an enum type. Only Postgres has enum types; other dialects declare enum columns inline,
+/// so for them this renders as a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateEnum {
+    pub schema: Option<String>,
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+impl CreateEnum {
+    pub fn from_enum(e: &Enum) -> CreateEnum {
+        CreateEnum {
+            schema: e.schema.clone(),
+            name: e.name.clone(),
+            variants: e.variants.clone(),
+        }
+    }
+}
+
+impl ToSql for CreateEnum {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        if dialect != Dialect::Postgres {
+            buf.push_str("/* enum ");
+            buf.push_str(&self.name);
+            buf.push_str(" is declared inline */");
+            return;
+        }
+        buf.push_str("CREATE TYPE ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str(" AS ENUM (");
+        buf.push_literal_sequence(&self.variants, ", ", dialect);
+        buf.push(')');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_enum() {
+        let e = Enum::new("mood", &["happy", "it's complicated"]);
+        assert_eq!(
+            CreateEnum::from_enum(&e).to_sql(Dialect::Postgres),
+            r#"CREATE TYPE "mood" AS ENUM ('happy', 'it''s complicated')"#
+        );
+    }
+}
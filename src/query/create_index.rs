@@ -1,8 +1,10 @@
 use crate::{Dialect, ToSql};
+use crate::schema::{Index, Table};
 use crate::util::SqlExtension;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum IndexType {
+    #[default]
     BTree,
     Hash,
     Gist,
@@ -10,12 +12,6 @@ pub enum IndexType {
     Brin,
 }
 
-impl Default for IndexType {
-    fn default() -> Self {
-        IndexType::BTree
-    }
-}
-
 /// Create index action for a table
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateIndex {
@@ -25,27 +21,81 @@ pub struct CreateIndex {
     pub table: String,
     pub columns: Vec<String>,
     pub type_: IndexType,
+    /// Build the index without blocking writes. Postgres only, and can't run in a transaction.
+    pub concurrently: bool,
+}
+
+impl CreateIndex {
+    pub fn from_index(table: &Table, index: &Index) -> Self {
+        CreateIndex {
+            name: index.name.clone(),
+            unique: false,
+            schema: table.schema.clone(),
+            table: table.name.clone(),
+            columns: index.columns.clone(),
+            type_: IndexType::default(),
+            concurrently: false,
+        }
+    }
+
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
 }
 
 impl ToSql for CreateIndex {
-    fn write_sql(&self, buf: &mut String, _: Dialect) {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         buf.push_str("CREATE ");
         if self.unique {
             buf.push_str("UNIQUE ");
         }
+        buf.push_str("INDEX ");
+        if self.concurrently && dialect == Dialect::Postgres {
+            buf.push_str("CONCURRENTLY ");
+        }
         buf.push_quoted(&self.name);
         buf.push_str(" ON ");
         buf.push_table_name(&self.schema, &self.table);
-        buf.push_str(" USING ");
-        match self.type_ {
-            IndexType::BTree => buf.push_str("BTREE"),
-            IndexType::Hash => buf.push_str("HASH"),
-            IndexType::Gist => buf.push_str("GIST"),
-            IndexType::SpGist => buf.push_str("SPGIST"),
-            IndexType::Brin => buf.push_str("BRIN"),
+        // Sqlite has a single index type
+        if dialect != Dialect::Sqlite {
+            buf.push_str(" USING ");
+            match self.type_ {
+                IndexType::BTree => buf.push_str("BTREE"),
+                IndexType::Hash => buf.push_str("HASH"),
+                IndexType::Gist => buf.push_str("GIST"),
+                IndexType::SpGist => buf.push_str("SPGIST"),
+                IndexType::Brin => buf.push_str("BRIN"),
+            }
         }
         buf.push_str(" (");
         buf.push_quoted_sequence(&self.columns, ", ");
         buf.push(')');
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_index() {
+        let index = CreateIndex {
+            name: "user_email_idx".to_string(),
+            unique: true,
+            schema: None,
+            table: "user".to_string(),
+            columns: vec!["email".to_string()],
+            type_: IndexType::BTree,
+            concurrently: true,
+        };
+        assert_eq!(
+            index.to_sql(Dialect::Postgres),
+            r#"CREATE UNIQUE INDEX CONCURRENTLY "user_email_idx" ON "user" USING BTREE ("email")"#
+        );
+        assert_eq!(
+            index.to_sql(Dialect::Sqlite),
+            r#"CREATE UNIQUE INDEX "user_email_idx" ON "user" ("email")"#
+        );
+    }
+}
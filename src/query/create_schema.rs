@@ -1,7 +1,7 @@
 use crate::{Dialect, ToSql};
 use crate::util::SqlExtension;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateSchema {
     pub name: String,
     pub if_not_exists: bool,
@@ -25,8 +25,19 @@ impl ToSql for CreateSchema {
     fn write_sql(&self, buf: &mut String, _: Dialect) {
         buf.push_str("CREATE SCHEMA ");
         if self.if_not_exists {
-            buf.push_str(" IF NOT EXISTS ");
+            buf.push_str("IF NOT EXISTS ");
         }
         buf.push_quoted(&self.name);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_schema() {
+        let c = CreateSchema::new("audit").if_not_exists();
+        assert_eq!(c.to_sql(Dialect::Postgres), r#"CREATE SCHEMA IF NOT EXISTS "audit""#);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::schema::{Sequence, SequenceOptions};
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Create a sequence. Only Postgres has sequences, so other dialects render a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateSequence {
+    pub schema: Option<String>,
+    pub name: String,
+    pub options: SequenceOptions,
+}
+
+impl CreateSequence {
+    pub fn from_sequence(sequence: &Sequence) -> CreateSequence {
+        CreateSequence {
+            schema: sequence.schema.clone(),
+            name: sequence.name.clone(),
+            options: sequence.options.clone(),
+        }
+    }
+}
+
+impl ToSql for CreateSequence {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        if dialect != Dialect::Postgres {
+            buf.push_str("/* sequence ");
+            buf.push_str(&self.name);
+            buf.push_str(" requires Postgres */");
+            return;
+        }
+        buf.push_str("CREATE SEQUENCE ");
+        buf.push_table_name(&self.schema, &self.name);
+        self.options.write_sql(buf, dialect, &self.schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_sequence() {
+        let s = Sequence::new("invoice_number")
+            .start(1000)
+            .increment(1)
+            .max_value(999999)
+            .cycle(false)
+            .owned_by("invoice", "number");
+        assert_eq!(
+            CreateSequence::from_sequence(&s).to_sql(Dialect::Postgres),
+            r#"CREATE SEQUENCE "invoice_number" INCREMENT BY 1 MAXVALUE 999999 START WITH 1000 NO CYCLE OWNED BY "invoice"."number""#
+        );
+    }
+}
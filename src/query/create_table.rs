@@ -1,3 +1,4 @@
+use crate::schema::{Enum, PrimaryKey, TableConstraint};
 use crate::util::SqlExtension;
 use crate::{Column, Dialect, Table, ToSql};
 
@@ -7,24 +8,130 @@ pub struct CreateTable {
     pub schema: Option<String>,
     pub name: String,
     pub columns: Vec<Column>,
+    /// Rendered as a table-level `PRIMARY KEY (...)` clause, after the columns.
+    pub primary_key: Option<PrimaryKey>,
+    pub constraints: Vec<TableConstraint>,
+    /// Definitions of the enum types used by the columns, for dialects that declare them inline.
+    pub enums: Vec<Enum>,
+    /// Only rendered for MySQL. Other dialects use `CommentOn`.
+    pub comment: Option<String>,
+    pub if_not_exists: bool,
 }
 
 impl CreateTable {
+    /// Single column primary keys are rendered inline on the column. Composite or named primary
+    /// keys are rendered as a table-level clause.
     pub fn from_table(table: &Table) -> CreateTable {
+        let mut columns = table.columns.clone();
+        let pkey_columns = table.primary_key_columns();
+        let primary_key = if table.primary_key.is_some() || pkey_columns.len() > 1 {
+            for column in &mut columns {
+                column.primary_key = false;
+            }
+            Some(PrimaryKey {
+                name: table.primary_key.as_ref().and_then(|pk| pk.name.clone()),
+                columns: pkey_columns,
+            })
+        } else {
+            None
+        };
         CreateTable {
             schema: table.schema.clone(),
             name: table.name.clone(),
-            columns: table.columns.clone(),
+            columns,
+            primary_key,
+            constraints: table.constraints.clone(),
+            enums: vec![],
+            comment: table.comment.clone(),
+            if_not_exists: false,
         }
     }
+
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
 }
 
 impl ToSql for CreateTable {
     fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         buf.push_str("CREATE TABLE ");
+        if self.if_not_exists {
+            buf.push_str("IF NOT EXISTS ");
+        }
         buf.push_table_name(&self.schema, &self.name);
         buf.push_str(" (\n");
-        buf.push_sql_sequence(&self.columns, ",\n", dialect);
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                buf.push_str(",\n");
+            }
+            column.write_sql_with_enums(buf, dialect, &self.enums);
+        }
+        if let Some(primary_key) = &self.primary_key {
+            buf.push_str(",\n");
+            buf.push_sql(primary_key, dialect);
+        }
+        for constraint in &self.constraints {
+            buf.push_str(",\n");
+            buf.push_sql(constraint, dialect);
+        }
         buf.push_str("\n)");
+        if let (Some(comment), Dialect::Mysql) = (&self.comment, dialect) {
+            buf.push_str(" COMMENT = ");
+            buf.push_literal(comment, dialect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Type;
+
+    #[test]
+    fn test_composite_primary_key() {
+        let column = |name: &str| Column::new(name, Type::I32).primary_key();
+        let table = Table::new("membership")
+            .column(column("team_id"))
+            .column(column("user_id"));
+        let sql = CreateTable::from_table(&table).to_sql(Dialect::Postgres);
+        assert_eq!(
+            sql,
+            r#"CREATE TABLE "membership" (
+"team_id" integer NOT NULL,
+"user_id" integer NOT NULL,
+PRIMARY KEY ("team_id", "user_id")
+)"#
+        );
+
+        let mut table = Table::new("user").column(column("id"));
+        let sql = CreateTable::from_table(&table).to_sql(Dialect::Postgres);
+        assert_eq!(sql, "CREATE TABLE \"user\" (\n\"id\" integer NOT NULL PRIMARY KEY\n)");
+
+        table.primary_key = Some(PrimaryKey::new(&["id"]).name("user_pk"));
+        let sql = CreateTable::from_table(&table).to_sql(Dialect::Postgres);
+        assert_eq!(
+            sql,
+            "CREATE TABLE \"user\" (\n\"id\" integer NOT NULL,\nCONSTRAINT \"user_pk\" PRIMARY KEY (\"id\")\n)"
+        );
+    }
+
+    #[test]
+    fn test_enum_column() {
+        let table = Table::new("user").column(Column::new("mood", Type::Enum("mood".to_string())));
+        let mut create = CreateTable::from_table(&table);
+        create.enums = vec![Enum::new("mood", &["happy", "sad"])];
+        assert_eq!(
+            create.to_sql(Dialect::Postgres),
+            "CREATE TABLE \"user\" (\n\"mood\" \"mood\"\n)"
+        );
+        assert_eq!(
+            create.to_sql(Dialect::Mysql),
+            "CREATE TABLE \"user\" (\n\"mood\" ENUM('happy', 'sad')\n)"
+        );
+        assert_eq!(
+            create.to_sql(Dialect::Sqlite),
+            "CREATE TABLE \"user\" (\n\"mood\" TEXT CHECK (\"mood\" IN ('happy', 'sad'))\n)"
+        );
     }
 }
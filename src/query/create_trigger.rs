@@ -0,0 +1,80 @@
+use crate::query::Expr;
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// A Postgres trigger, and the function it runs, that sets `column` of each row inserted or
+/// updated to `value`, e.g. to keep a copy of a column in sync while it's backfilled. `value` can
+/// refer to the other columns of the row by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateTrigger {
+    pub schema: Option<String>,
+    pub table: String,
+    /// The name of both the trigger and its function.
+    pub name: String,
+    pub column: String,
+    pub value: Expr,
+}
+
+impl CreateTrigger {
+    pub fn new(name: &str, table: &str, column: &str, value: impl Into<Expr>) -> Self {
+        CreateTrigger {
+            schema: None,
+            table: table.to_string(),
+            name: name.to_string(),
+            column: column.to_string(),
+            value: value.into(),
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+}
+
+impl ToSql for CreateTrigger {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("CREATE FUNCTION ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str("() RETURNS trigger LANGUAGE plpgsql AS $sqlmo$ BEGIN NEW.");
+        buf.push_quoted(&self.column);
+        buf.push_str(" := (SELECT ");
+        buf.push_sql(&self.value, dialect);
+        // the row's columns are selected under the table's name, so `value` reads them
+        buf.push_str(" FROM (SELECT NEW.*) AS ");
+        buf.push_quoted(&self.table);
+        buf.push_str("); RETURN NEW; END $sqlmo$; CREATE TRIGGER ");
+        buf.push_quoted(&self.name);
+        buf.push_str(" BEFORE INSERT OR UPDATE ON ");
+        buf.push_table_name(&self.schema, &self.table);
+        buf.push_str(" FOR EACH ROW EXECUTE FUNCTION ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str("()");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_trigger() {
+        let trigger = CreateTrigger::new(
+            "user_age_sync",
+            "user",
+            "age_new",
+            Expr::Raw(r#""age"::bigint"#.to_string()),
+        )
+        .schema(Some("auth".to_string()));
+        assert_eq!(
+            trigger.to_sql(Dialect::Postgres),
+            concat!(
+                r#"CREATE FUNCTION "auth"."user_age_sync"() RETURNS trigger LANGUAGE plpgsql"#,
+                r#" AS $sqlmo$ BEGIN NEW."age_new" := (SELECT "age"::bigint"#,
+                r#" FROM (SELECT NEW.*) AS "user"); RETURN NEW; END $sqlmo$;"#,
+                r#" CREATE TRIGGER "user_age_sync" BEFORE INSERT OR UPDATE ON "auth"."user""#,
+                r#" FOR EACH ROW EXECUTE FUNCTION "auth"."user_age_sync"()"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use crate::schema::{View, ViewQuery};
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Create a view. Materialized views are Postgres only, and render as regular views for other
+/// dialects. Sqlite doesn't support `OR REPLACE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateView {
+    pub schema: Option<String>,
+    pub name: String,
+    pub query: ViewQuery,
+    pub materialized: bool,
+    pub or_replace: bool,
+}
+
+impl CreateView {
+    pub fn from_view(view: &View) -> CreateView {
+        CreateView {
+            schema: view.schema.clone(),
+            name: view.name.clone(),
+            query: view.query.clone(),
+            materialized: view.materialized,
+            or_replace: false,
+        }
+    }
+
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+}
+
+impl ToSql for CreateView {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("CREATE ");
+        if self.or_replace && dialect != Dialect::Sqlite {
+            buf.push_str("OR REPLACE ");
+        }
+        if self.materialized && dialect == Dialect::Postgres {
+            buf.push_str("MATERIALIZED ");
+        }
+        buf.push_str("VIEW ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str(" AS ");
+        buf.push_sql(&self.query, dialect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Select;
+
+    #[test]
+    fn test_create_view() {
+        let view = View::new("active_user", Select::default().select_raw("*").from("user"));
+        assert_eq!(
+            CreateView::from_view(&view).or_replace().to_sql(Dialect::Postgres),
+            r#"CREATE OR REPLACE VIEW "active_user" AS SELECT * FROM "user" "#
+        );
+        let view = View::new("team_size", "SELECT team_id, count(*) FROM member GROUP BY 1")
+            .materialized()
+            .schema("report");
+        assert_eq!(
+            CreateView::from_view(&view).to_sql(Dialect::Postgres),
+            r#"CREATE MATERIALIZED VIEW "report"."team_size" AS SELECT team_id, count(*) FROM member GROUP BY 1"#
+        );
+    }
+}
@@ -17,21 +17,21 @@ impl ToSql for CteQuery {
     }
 }
 
-impl Into<CteQuery> for Select {
-    fn into(self) -> CteQuery {
-        CteQuery::Select(self)
+impl From<Select> for CteQuery {
+    fn from(select: Select) -> Self {
+        CteQuery::Select(select)
     }
 }
 
-impl Into<CteQuery> for Insert {
-    fn into(self) -> CteQuery {
-        CteQuery::Insert(self)
+impl From<Insert> for CteQuery {
+    fn from(insert: Insert) -> Self {
+        CteQuery::Insert(insert)
     }
 }
 
-impl Into<CteQuery> for String {
-    fn into(self) -> CteQuery {
-        CteQuery::Raw(self)
+impl From<String> for CteQuery {
+    fn from(raw: String) -> Self {
+        CteQuery::Raw(raw)
     }
 }
 
@@ -0,0 +1,22 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Drop an enum type. Like `CreateEnum`, only Postgres has enum types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropEnum {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl ToSql for DropEnum {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        if dialect != Dialect::Postgres {
+            buf.push_str("/* enum ");
+            buf.push_str(&self.name);
+            buf.push_str(" is declared inline */");
+            return;
+        }
+        buf.push_str("DROP TYPE ");
+        buf.push_table_name(&self.schema, &self.name);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Drop an index. MySQL needs the table the index is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropIndex {
+    pub schema: Option<String>,
+    pub name: String,
+    pub table: String,
+}
+
+impl ToSql for DropIndex {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("DROP INDEX ");
+        match dialect {
+            Dialect::Mysql => {
+                buf.push_quoted(&self.name);
+                buf.push_str(" ON ");
+                buf.push_table_name(&self.schema, &self.table);
+            }
+            _ => buf.push_table_name(&self.schema, &self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_index() {
+        let d = DropIndex {
+            schema: Some("auth".to_string()),
+            name: "user_email_idx".to_string(),
+            table: "user".to_string(),
+        };
+        assert_eq!(d.to_sql(Dialect::Postgres), r#"DROP INDEX "auth"."user_email_idx""#);
+        assert_eq!(
+            d.to_sql(Dialect::Mysql),
+            r#"DROP INDEX "user_email_idx" ON "auth"."user""#
+        );
+    }
+}
@@ -0,0 +1,23 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Drop a schema. Fails if it still contains objects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropSchema {
+    pub name: String,
+}
+
+impl DropSchema {
+    pub fn new(name: &str) -> Self {
+        DropSchema {
+            name: name.to_string(),
+        }
+    }
+}
+
+impl ToSql for DropSchema {
+    fn write_sql(&self, buf: &mut String, _: Dialect) {
+        buf.push_str("DROP SCHEMA ");
+        buf.push_quoted(&self.name);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropSequence {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl ToSql for DropSequence {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        buf.push_str("DROP SEQUENCE ");
+        buf.push_table_name(&self.schema, &self.name);
+    }
+}
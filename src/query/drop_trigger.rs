@@ -0,0 +1,37 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Drop a trigger made by `CreateTrigger`, and its function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropTrigger {
+    pub schema: Option<String>,
+    pub table: String,
+    pub name: String,
+}
+
+impl DropTrigger {
+    pub fn new(name: &str, table: &str) -> Self {
+        DropTrigger {
+            schema: None,
+            table: table.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+}
+
+impl ToSql for DropTrigger {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        buf.push_str("DROP TRIGGER ");
+        buf.push_quoted(&self.name);
+        buf.push_str(" ON ");
+        buf.push_table_name(&self.schema, &self.table);
+        buf.push_str("; DROP FUNCTION ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str("()");
+    }
+}
@@ -0,0 +1,31 @@
+use crate::schema::View;
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropView {
+    pub schema: Option<String>,
+    pub name: String,
+    pub materialized: bool,
+}
+
+impl DropView {
+    pub fn from_view(view: &View) -> DropView {
+        DropView {
+            schema: view.schema.clone(),
+            name: view.name.clone(),
+            materialized: view.materialized,
+        }
+    }
+}
+
+impl ToSql for DropView {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("DROP ");
+        if self.materialized && dialect == Dialect::Postgres {
+            buf.push_str("MATERIALIZED ");
+        }
+        buf.push_str("VIEW ");
+        buf.push_table_name(&self.schema, &self.name);
+    }
+}
@@ -3,9 +3,10 @@ use crate::util::SqlExtension;
 use crate::{Dialect, Select, ToSql};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum OnConflict {
     Ignore,
+    #[default]
     Abort,
     /// Only valid for Sqlite, because we
     Replace,
@@ -72,12 +73,6 @@ impl Conflict {
     }
 }
 
-impl Default for OnConflict {
-    fn default() -> Self {
-        OnConflict::Abort
-    }
-}
-
 impl ToSql for Conflict {
     fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
         match self {
@@ -127,12 +122,12 @@ impl ToSql for Values {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Value(Vec<String>);
 
 impl Value {
     pub fn with(values: &[&str]) -> Self {
-        Self(values.into_iter().map(|v| v.to_string()).collect())
+        Self(values.iter().map(|v| v.to_string()).collect())
     }
 
     pub fn new() -> Self {
@@ -163,6 +158,7 @@ impl From<Vec<String>> for Value {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Values {
     Values(Vec<Value>),
     Select(Select),
@@ -171,7 +167,7 @@ pub enum Values {
 
 impl From<&[&[&'static str]]> for Values {
     fn from(values: &[&[&'static str]]) -> Self {
-        Self::Values(values.into_iter().map(|v| Value::with(v)).collect())
+        Self::Values(values.iter().map(|v| Value::with(v)).collect())
     }
 }
 
@@ -299,7 +295,7 @@ impl ToSql for Insert {
                     buf.push_sql(conflict, dialect);
                     buf.push_str(" DO UPDATE SET ");
                     let updates: Vec<Expr> = updates
-                        .into_iter()
+                        .iter()
                         .map(|(c, v)| Expr::new_eq(Expr::column(c), v.clone()))
                         .collect();
                     buf.push_sql_sequence(&updates, ", ", dialect);
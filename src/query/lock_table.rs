@@ -0,0 +1,46 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Lock a Postgres table in `ACCESS EXCLUSIVE` mode until the end of the transaction, blocking
+/// reads and writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockTable {
+    pub schema: Option<String>,
+    pub name: String,
+}
+
+impl LockTable {
+    pub fn new(name: &str) -> Self {
+        LockTable {
+            schema: None,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+}
+
+impl ToSql for LockTable {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        buf.push_str("LOCK TABLE ");
+        buf.push_table_name(&self.schema, &self.name);
+        buf.push_str(" IN ACCESS EXCLUSIVE MODE");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_table() {
+        let lock = LockTable::new("user").schema(Some("auth".to_string()));
+        assert_eq!(
+            lock.to_sql(Dialect::Postgres),
+            r#"LOCK TABLE "auth"."user" IN ACCESS EXCLUSIVE MODE"#
+        );
+    }
+}
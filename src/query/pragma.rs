@@ -0,0 +1,39 @@
+use crate::{Dialect, ToSql};
+
+/// A SQLite `PRAGMA`, e.g. to turn foreign key enforcement off while tables are rebuilt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pragma {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl Pragma {
+    pub fn new(name: &str) -> Self {
+        Pragma {
+            name: name.to_string(),
+            value: None,
+        }
+    }
+
+    pub fn value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Whether the pragma can run inside a transaction. Setting `foreign_keys` has no effect
+    /// there.
+    pub fn is_transactional(&self) -> bool {
+        !(self.name == "foreign_keys" && self.value.is_some())
+    }
+}
+
+impl ToSql for Pragma {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        buf.push_str("PRAGMA ");
+        buf.push_str(&self.name);
+        if let Some(value) = &self.value {
+            buf.push_str(" = ");
+            buf.push_str(value);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use crate::query::{CreateIndex, CreateTable, Expr};
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Rebuild a table into a new definition, for changes SQLite can't make with `ALTER TABLE`:
+/// the new table is created under a temporary name, the rows are copied into it, the old table
+/// is dropped, the new one is renamed into its place, and the indexes are recreated. Renders as
+/// several statements, ending with a check that fails if any rows in the database don't satisfy a
+/// foreign key, so the transaction running them can be rolled back.
+///
+/// Foreign keys have to be turned off while it runs, or dropping the old table deletes the rows
+/// referencing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildTable {
+    /// The table as it's rebuilt, under its own name.
+    pub table: CreateTable,
+    /// The columns filled from the old table, and the value of each. Other columns get their
+    /// default.
+    pub columns: Vec<(String, Expr)>,
+    pub indexes: Vec<CreateIndex>,
+}
+
+impl RebuildTable {
+    /// The name the new table is created under.
+    pub fn temporary_name(&self) -> String {
+        format!("{}__sqlmo_new", self.table.name)
+    }
+}
+
+/// The temporary table the foreign key violations are counted into.
+const FOREIGN_KEY_CHECK: &str = "sqlmo_foreign_key_check";
+
+impl ToSql for RebuildTable {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        let schema = &self.table.schema;
+        let name = &self.table.name;
+        let temporary = CreateTable {
+            name: self.temporary_name(),
+            ..self.table.clone()
+        };
+        buf.push_sql(&temporary, dialect);
+        buf.push_str(";\nINSERT INTO ");
+        buf.push_table_name(schema, &temporary.name);
+        buf.push_str(" (");
+        let columns = self.columns.iter().map(|(c, _)| c.clone()).collect::<Vec<_>>();
+        buf.push_quoted_sequence(&columns, ", ");
+        buf.push_str(") SELECT ");
+        let values = self.columns.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>();
+        buf.push_sql_sequence(&values, ", ", dialect);
+        buf.push_str(" FROM ");
+        buf.push_table_name(schema, name);
+        buf.push_str(";\nDROP TABLE ");
+        buf.push_table_name(schema, name);
+        buf.push_str(";\nALTER TABLE ");
+        buf.push_table_name(schema, &temporary.name);
+        buf.push_str(" RENAME TO ");
+        buf.push_quoted(name);
+        for index in &self.indexes {
+            buf.push_str(";\n");
+            buf.push_sql(index, dialect);
+        }
+        // `PRAGMA foreign_key_check` returns the violations, which a CHECK turns into an error.
+        // The whole schema is checked, since other tables' rows may reference this one.
+        buf.push_str(";\nCREATE TEMP TABLE ");
+        buf.push_quoted(FOREIGN_KEY_CHECK);
+        buf.push_str(r#" ("violations" INTEGER CHECK ("violations" = 0));"#);
+        buf.push_str("\nINSERT INTO ");
+        buf.push_quoted(FOREIGN_KEY_CHECK);
+        buf.push_str(" SELECT count(*) FROM pragma_foreign_key_check(");
+        if let Some(schema) = schema {
+            buf.push_str("NULL, ");
+            buf.push_literal(schema, Dialect::Sqlite);
+        }
+        buf.push_str(");\nDROP TABLE ");
+        buf.push_quoted(FOREIGN_KEY_CHECK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Index;
+    use crate::{Column, Table, Type};
+
+    #[test]
+    fn test_rebuild_table() {
+        let table = Table::new("user")
+            .column(Column::new("id", Type::I64).primary_key())
+            .index(Index {
+                name: "user_id_idx".to_string(),
+                columns: vec!["id".to_string()],
+            });
+        let rebuild = RebuildTable {
+            table: CreateTable::from_table(&table),
+            columns: vec![("id".to_string(), Expr::column("id"))],
+            indexes: vec![CreateIndex::from_index(&table, &table.indexes[0])],
+        };
+        assert_eq!(
+            rebuild.to_sql(Dialect::Sqlite),
+            r#"CREATE TABLE "user__sqlmo_new" (
+"id" INTEGER NOT NULL PRIMARY KEY
+);
+INSERT INTO "user__sqlmo_new" ("id") SELECT "id" FROM "user";
+DROP TABLE "user";
+ALTER TABLE "user__sqlmo_new" RENAME TO "user";
+CREATE INDEX "user_id_idx" ON "user" ("id");
+CREATE TEMP TABLE "sqlmo_foreign_key_check" ("violations" INTEGER CHECK ("violations" = 0));
+INSERT INTO "sqlmo_foreign_key_check" SELECT count(*) FROM pragma_foreign_key_check();
+DROP TABLE "sqlmo_foreign_key_check""#
+        );
+    }
+}
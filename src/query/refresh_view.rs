@@ -0,0 +1,54 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// Refresh a Postgres materialized view. `CONCURRENTLY` requires a unique index on the view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshMaterializedView {
+    pub schema: Option<String>,
+    pub name: String,
+    pub concurrently: bool,
+}
+
+impl RefreshMaterializedView {
+    pub fn new(name: &str) -> Self {
+        Self {
+            schema: None,
+            name: name.to_string(),
+            concurrently: false,
+        }
+    }
+
+    pub fn schema(mut self, schema: Option<String>) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
+}
+
+impl ToSql for RefreshMaterializedView {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        buf.push_str("REFRESH MATERIALIZED VIEW ");
+        if self.concurrently {
+            buf.push_str("CONCURRENTLY ");
+        }
+        buf.push_table_name(&self.schema, &self.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh() {
+        let r = RefreshMaterializedView::new("team_size").concurrently();
+        assert_eq!(
+            r.to_sql(Dialect::Postgres),
+            r#"REFRESH MATERIALIZED VIEW CONCURRENTLY "team_size""#
+        );
+    }
+}
@@ -308,11 +308,11 @@ impl ToSql for Where {
     fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         match self {
             Where::And(v) => {
-                buf.push_sql_sequence(&v, " AND ", dialect);
+                buf.push_sql_sequence(v, " AND ", dialect);
             }
             Where::Or(v) => {
                 buf.push('(');
-                buf.push_sql_sequence(&v, " OR ", dialect);
+                buf.push_sql_sequence(v, " OR ", dialect);
                 buf.push(')');
             }
             #[allow(deprecated)]
@@ -327,8 +327,9 @@ impl ToSql for Where {
 }
 
 /// The direction of a column in an ORDER BY clause.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Direction {
+    #[default]
     Asc,
     Desc,
 }
@@ -405,12 +406,6 @@ impl ToSql for OrderBy {
     }
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        Direction::Asc
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GroupBy(String);
 
@@ -113,11 +113,144 @@ impl Expr {
     pub fn not_distinct_from<L: Into<Expr>, R: Into<Expr>>(left: L, right: R) -> Self {
         Self::NotDistinctFrom(Box::new(left.into()), Box::new(right.into()))
     }
+
+    /// Compare two expressions, ignoring the differences Postgres introduces when it reports a
+    /// stored expression back, e.g. `'foo'::text` compares equal to `'foo'`, and `NOW()` to `now()`.
+    pub fn lossy_eq(&self, other: &Expr) -> bool {
+        self == other
+            || normalize_sql(&self.to_sql(Dialect::Postgres))
+                == normalize_sql(&other.to_sql(Dialect::Postgres))
+    }
 }
 
-impl Into<Expr> for &str {
-    fn into(self) -> Expr {
-        Expr::Raw(self.to_string())
+/// Words that can continue a multi-word type name in a cast, e.g. `::character varying`.
+const TYPE_CONTINUATION_WORDS: &[&str] = &["varying", "precision", "with", "without", "time", "zone"];
+
+/// Normalize a SQL expression for comparison: casts of literals are removed, enclosing
+/// parentheses are stripped, and everything outside string literals and quoted identifiers is
+/// lowercased. Quoted identifiers that don't need quoting are unquoted.
+fn normalize_sql(s: &str) -> String {
+    let is_ident = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '.';
+    let chars: Vec<char> = s.trim().chars().collect();
+    let mut out = String::new();
+    // where the last token starts, if it's a literal, e.g. `'foo'`, `1` or `NULL`
+    let mut literal = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' || c == '"' {
+            // copied verbatim. Escaped quotes ('') are handled by re-entering.
+            let start = i;
+            i += 1;
+            while i < chars.len() {
+                i += 1;
+                if chars[i - 1] == c {
+                    break;
+                }
+            }
+            let quoted = chars[start..i].iter().collect::<String>();
+            let inner = quoted.trim_matches('"');
+            let plain = inner.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+                && inner
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            let start = out.len();
+            out.push_str(if c == '"' && plain { inner } else { &quoted });
+            literal = (c == '\'').then_some(start);
+        } else if c == ':' && chars.get(i + 1) == Some(&':') && literal.is_some() {
+            i = skip_cast(&chars, i + 2);
+        } else if is_ident(&c) {
+            let start = i;
+            while chars.get(i).is_some_and(is_ident) {
+                i += 1;
+            }
+            let word = chars[start..i].iter().collect::<String>().to_lowercase();
+            let is_literal = word == "null" || word.parse::<f64>().is_ok();
+            literal = is_literal.then_some(out.len());
+            out.push_str(&word);
+        } else if let (')', Some(start)) = (c, literal.filter(|s| out[..*s].ends_with('('))) {
+            // a parenthesized literal, e.g. `(0)::numeric`
+            out.remove(start - 1);
+            literal = Some(start - 1);
+            i += 1;
+        } else {
+            out.extend(c.to_lowercase());
+            literal = None;
+            i += 1;
+        }
+    }
+    let mut out = out.trim();
+    while out.starts_with('(') && out.ends_with(')') && parens_enclose(out) {
+        out = out[1..out.len() - 1].trim();
+    }
+    out.to_string()
+}
+
+/// Returns the index after the type name of a cast starting at `i` (just past the `::`).
+fn skip_cast(chars: &[char], mut i: usize) -> usize {
+    let is_ident = |c: &char| c.is_alphanumeric() || *c == '_' || *c == '.';
+    if chars.get(i) == Some(&'"') {
+        i += 1;
+        while i < chars.len() && chars[i] != '"' {
+            i += 1;
+        }
+        i += 1;
+    } else {
+        while chars.get(i).is_some_and(is_ident) {
+            i += 1;
+        }
+    }
+    loop {
+        let mut j = i;
+        while chars.get(j) == Some(&' ') {
+            j += 1;
+        }
+        let start = j;
+        while chars.get(j).is_some_and(is_ident) {
+            j += 1;
+        }
+        let word: String = chars[start..j].iter().collect::<String>().to_lowercase();
+        if j > start && TYPE_CONTINUATION_WORDS.contains(&word.as_str()) {
+            i = j;
+        } else {
+            break;
+        }
+    }
+    if chars.get(i) == Some(&'(') {
+        while i < chars.len() && chars[i] != ')' {
+            i += 1;
+        }
+        i += 1;
+    }
+    while chars.get(i) == Some(&'[') && chars.get(i + 1) == Some(&']') {
+        i += 2;
+    }
+    i.min(chars.len())
+}
+
+/// Whether the opening paren at the start of `s` is closed by the paren at the end of `s`.
+fn parens_enclose(s: &str) -> bool {
+    let mut depth = 0;
+    let mut in_quote = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '\'' => in_quote = !in_quote,
+            '(' if !in_quote => depth += 1,
+            ')' if !in_quote => {
+                depth -= 1;
+                if depth == 0 {
+                    return i == s.len() - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+impl From<&str> for Expr {
+    fn from(raw: &str) -> Self {
+        Expr::Raw(raw.to_string())
     }
 }
 
@@ -127,7 +260,7 @@ impl ToSql for Expr {
             Expr::Case(c) => c.write_sql(buf, dialect),
             Expr::And(and) => {
                 buf.push('(');
-                buf.push_sql_sequence(&and, " AND ", dialect);
+                buf.push_sql_sequence(and, " AND ", dialect);
                 buf.push(')');
             }
             Expr::Raw(a) => buf.push_str(a),
@@ -171,3 +304,25 @@ impl ToSql for Operation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lossy_eq() {
+        assert!(Expr::from("'foo'::text").lossy_eq(&"'foo'".into()));
+        assert!(Expr::from("'foo'::character varying").lossy_eq(&"'foo'".into()));
+        assert!(Expr::from("('{}'::jsonb)").lossy_eq(&"'{}'".into()));
+        assert!(Expr::from("now()").lossy_eq(&"NOW()".into()));
+        assert!(Expr::from("'2020-01-01'::timestamp with time zone").lossy_eq(&"'2020-01-01'".into()));
+        assert!(!Expr::from("'Foo'::text").lossy_eq(&"'foo'".into()));
+        assert!(!Expr::from("now()").lossy_eq(&"clock_timestamp()".into()));
+        assert!(!Expr::from("(1) + (2)").lossy_eq(&"1) + (2".into()));
+        assert!(Expr::from("NULL::character varying").lossy_eq(&"null".into()));
+        assert!(Expr::from("(price > (0)::numeric)").lossy_eq(&"price > 0".into()));
+        assert!(!Expr::from("now()::date").lossy_eq(&"now()".into()));
+        assert!(Expr::from(r#""price" > 0"#).lossy_eq(&"price > 0".into()));
+        assert!(!Expr::from(r#""Price" > 0"#).lossy_eq(&"price > 0".into()));
+    }
+}
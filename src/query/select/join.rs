@@ -3,6 +3,7 @@ use crate::util::SqlExtension;
 use crate::{Dialect, Select, ToSql};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum JoinTable {
     Select(Select),
     Table {
@@ -11,20 +12,15 @@ pub enum JoinTable {
     },
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
 pub enum JoinType {
+    #[default]
     Inner,
     Left,
     Right,
     Full,
 }
 
-impl Default for JoinType {
-    fn default() -> Self {
-        JoinType::Inner
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Criteria {
     On(Where),
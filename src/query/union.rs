@@ -11,7 +11,7 @@ impl ToSql for Union {
         if self.queries.is_empty() {
             return;
         }
-        let first = self.queries.iter().next().unwrap();
+        let first = self.queries.first().unwrap();
         first.write_sql(buf, dialect);
         for q in self.queries.iter().skip(1) {
             buf.push_str(" UNION ");
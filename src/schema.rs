@@ -1,13 +1,27 @@
 mod table;
 mod r#type;
 mod column;
+mod conversion;
 mod index;
 mod constraint;
+mod r#enum;
+mod sequence;
+mod view;
 
-pub use column::Column;
+pub use column::{Column, Generated};
+pub use conversion::Conversion;
+pub use index::Index;
+pub use r#enum::Enum;
 pub use r#type::Type;
+pub use sequence::{OwnedBy, Sequence, SequenceOptions};
 pub use table::Table;
-pub use constraint::{Constraint, ForeignKey};
+pub use view::{View, ViewQuery};
+pub use constraint::{
+    Constraint, Deferrable, Exclude, ExcludeElement, ForeignKey, MatchType, PrimaryKey,
+    ReferentialAction, TableConstraint,
+};
+
+use std::collections::BTreeSet;
 
 use anyhow::Result;
 use crate::migrate::{Migration, migrate, MigrationOptions};
@@ -17,6 +31,12 @@ use crate::migrate::{Migration, migrate, MigrationOptions};
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Schema {
     pub tables: Vec<Table>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub enums: Vec<Enum>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub views: Vec<View>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub sequences: Vec<Sequence>,
 }
 
 impl Schema {
@@ -25,10 +45,48 @@ impl Schema {
         migrate(self, desired, options)
     }
 
-    /// Propagate the schema name to all tables.
-    pub fn name_schema(&mut self, schema: &str) {
-        for table in &mut self.tables {
-            table.schema = Some(schema.to_string());
+    /// Place tables, enums, views and sequences without an explicit schema in `schema`.
+    pub fn default_schema(&mut self, schema: &str) {
+        let set = |s: &mut Option<String>| {
+            s.get_or_insert_with(|| schema.to_string());
+        };
+        self.tables.iter_mut().for_each(|t| set(&mut t.schema));
+        self.enums.iter_mut().for_each(|e| set(&mut e.schema));
+        self.views.iter_mut().for_each(|v| set(&mut v.schema));
+        self.sequences.iter_mut().for_each(|s| set(&mut s.schema));
+    }
+
+    /// The schemas that tables, enums, views and sequences are placed in, with `default` for
+    /// those without one.
+    pub fn namespaces<'a>(&'a self, default: &'a str) -> BTreeSet<&'a str> {
+        let tables = self.tables.iter().map(|t| &t.schema);
+        let enums = self.enums.iter().map(|e| &e.schema);
+        let views = self.views.iter().map(|v| &v.schema);
+        let sequences = self.sequences.iter().map(|s| &s.schema);
+        tables
+            .chain(enums)
+            .chain(views)
+            .chain(sequences)
+            .map(|s| s.as_deref().unwrap_or(default))
+            .collect()
+    }
+
+    /// The enum definitions used by the columns of `table`, preferring those in its schema.
+    pub fn enums_for(&self, table: &Table) -> Vec<Enum> {
+        let mut enums = Vec::<Enum>::new();
+        for name in table.columns.iter().filter_map(|c| c.typ.enum_name()) {
+            if enums.iter().any(|e| e.name == name) {
+                continue;
+            }
+            let named = |e: &&Enum| e.name == name;
+            let e = self
+                .enums
+                .iter()
+                .filter(named)
+                .find(|e| e.schema == table.schema)
+                .or_else(|| self.enums.iter().find(named));
+            enums.extend(e.cloned());
         }
+        enums
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use crate::{Dialect, ToSql, Type};
 use crate::query::Expr;
 use crate::schema::constraint::Constraint;
+use crate::schema::Enum;
 use crate::util::SqlExtension;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,21 +14,127 @@ pub struct Column {
     #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
     pub default: Option<Expr>,
     #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
-    pub constraint: Option<Constraint>
+    pub constraint: Option<Constraint>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub generated: Option<Generated>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub comment: Option<String>,
 }
 
+/// A column whose value is generated by the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type"))]
+pub enum Generated {
+    /// `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY`. Rendered as `AUTO_INCREMENT` in MySQL,
+    /// and as `AUTOINCREMENT` on Sqlite primary keys.
+    Identity { always: bool },
+    /// `GENERATED ALWAYS AS (expr) STORED`
+    Stored(Expr),
+}
 
-impl ToSql for Column {
-    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+impl Generated {
+    pub fn is_identity(&self) -> bool {
+        matches!(self, Generated::Identity { .. })
+    }
+
+    pub fn lossy_eq(&self, other: &Generated) -> bool {
+        match (self, other) {
+            (Generated::Stored(a), Generated::Stored(b)) => a.lossy_eq(b),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl Column {
+    /// A nullable column with no key, default or constraint.
+    pub fn new(name: &str, typ: Type) -> Column {
+        Column {
+            name: name.to_string(),
+            typ,
+            nullable: true,
+            primary_key: false,
+            default: None,
+            constraint: None,
+            generated: None,
+            comment: None,
+        }
+    }
+
+    pub fn not_null(mut self) -> Self {
+        self.nullable = false;
+        self
+    }
+
+    /// Make the column the primary key, which also makes it `NOT NULL`.
+    pub fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self.nullable = false;
+        self
+    }
+
+    pub fn default<E: Into<Expr>>(mut self, default: E) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+
+    pub fn generated(mut self, generated: Generated) -> Self {
+        self.generated = Some(generated);
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Write the column definition, using `enums` to declare enum columns inline for dialects
+    /// without enum types.
+    pub(crate) fn write_sql_with_enums(&self, buf: &mut String, dialect: Dialect, enums: &[Enum]) {
         buf.push_quoted(&self.name);
         buf.push(' ');
-        buf.push_str(&self.typ.to_sql(dialect));
+        let e = match &self.typ {
+            Type::Enum(name) if dialect != Dialect::Postgres => {
+                enums.iter().find(|e| &e.name == name)
+            }
+            _ => None,
+        };
+        let autoincrement = dialect == Dialect::Sqlite
+            && self.primary_key
+            && matches!(self.generated, Some(Generated::Identity { .. }));
+        match e {
+            Some(e) => e.write_inline_type(buf, dialect, &self.name),
+            // Sqlite only allows `AUTOINCREMENT` on exactly `INTEGER`
+            None if autoincrement => buf.push_str("INTEGER"),
+            None => self.typ.write_sql_with_enums(buf, dialect, enums),
+        }
+        // MySQL requires the generated expression right after the type
+        if let Some(Generated::Stored(expr)) = &self.generated {
+            buf.push_str(" GENERATED ALWAYS AS (");
+            buf.push_sql(expr, dialect);
+            buf.push_str(") STORED");
+        }
         if !self.nullable {
             buf.push_str(" NOT NULL");
         }
         if self.primary_key {
             buf.push_str(" PRIMARY KEY");
         }
+        if let Some(Generated::Identity { always }) = &self.generated {
+            match dialect {
+                Dialect::Postgres if *always => buf.push_str(" GENERATED ALWAYS AS IDENTITY"),
+                Dialect::Postgres => buf.push_str(" GENERATED BY DEFAULT AS IDENTITY"),
+                Dialect::Mysql => buf.push_str(" AUTO_INCREMENT"),
+                // Sqlite only allows this on an INTEGER PRIMARY KEY, which is already an alias of
+                // the auto-incrementing rowid.
+                Dialect::Sqlite if autoincrement => buf.push_str(" AUTOINCREMENT"),
+                Dialect::Sqlite => {}
+            }
+        }
         if let Some(default) = &self.default {
             buf.push_str(" DEFAULT ");
             buf.push_sql(default, dialect);
@@ -36,5 +143,54 @@ impl ToSql for Column {
             buf.push(' ');
             buf.push_sql(constraint, dialect);
         }
+        // other dialects use `COMMENT ON`, see `query::CommentOn`
+        if let (Some(comment), Dialect::Mysql) = (&self.comment, dialect) {
+            buf.push_str(" COMMENT ");
+            buf.push_literal(comment, dialect);
+        }
     }
-}
\ No newline at end of file
+}
+
+impl ToSql for Column {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        self.write_sql_with_enums(buf, dialect, &[]);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated() {
+        let mut column = Column::new("id", Type::I64)
+            .primary_key()
+            .generated(Generated::Identity { always: true });
+        assert_eq!(
+            column.to_sql(Dialect::Postgres),
+            r#""id" bigint NOT NULL PRIMARY KEY GENERATED ALWAYS AS IDENTITY"#
+        );
+        assert_eq!(
+            column.to_sql(Dialect::Mysql),
+            r#""id" BIGINT NOT NULL PRIMARY KEY AUTO_INCREMENT"#
+        );
+        assert_eq!(
+            column.to_sql(Dialect::Sqlite),
+            r#""id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT"#
+        );
+        let id = Column::new("id", Type::I32)
+            .primary_key()
+            .generated(Generated::Identity { always: false });
+        assert_eq!(
+            id.to_sql(Dialect::Sqlite),
+            r#""id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT"#
+        );
+
+        column.name = "total".to_string();
+        column.primary_key = false;
+        column.generated = Some(Generated::Stored(Expr::Raw("price * quantity".to_string())));
+        assert_eq!(
+            column.to_sql(Dialect::Postgres),
+            r#""total" bigint GENERATED ALWAYS AS (price * quantity) STORED NOT NULL"#
+        );
+    }
+}
@@ -1,11 +1,223 @@
 use crate::{Dialect, ToSql};
+use crate::query::Expr;
 use crate::util::SqlExtension;
 
+/// Action taken on the referencing rows when a referenced row is deleted or updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReferentialAction {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+impl ToSql for ReferentialAction {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        use ReferentialAction::*;
+        buf.push_str(match self {
+            NoAction => "NO ACTION",
+            Restrict => "RESTRICT",
+            Cascade => "CASCADE",
+            SetNull => "SET NULL",
+            SetDefault => "SET DEFAULT",
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchType {
+    Full,
+    Partial,
+    Simple,
+}
+
+/// When a deferrable constraint is checked. A constraint that isn't deferrable is represented
+/// by `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Deferrable {
+    InitiallyImmediate,
+    InitiallyDeferred,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForeignKey {
+    /// Schema of the referenced table.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub schema: Option<String>,
     pub table: String,
+    /// Referenced columns.
     pub columns: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub on_delete: Option<ReferentialAction>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub on_update: Option<ReferentialAction>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub match_type: Option<MatchType>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub deferrable: Option<Deferrable>,
+}
+
+impl ForeignKey {
+    pub fn new(table: &str, columns: &[&str]) -> Self {
+        ForeignKey {
+            schema: None,
+            table: table.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            on_delete: None,
+            on_update: None,
+            match_type: None,
+            deferrable: None,
+        }
+    }
+
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    pub fn on_delete(mut self, action: ReferentialAction) -> Self {
+        self.on_delete = Some(action);
+        self
+    }
+
+    pub fn on_update(mut self, action: ReferentialAction) -> Self {
+        self.on_update = Some(action);
+        self
+    }
+
+    pub fn match_type(mut self, match_type: MatchType) -> Self {
+        self.match_type = Some(match_type);
+        self
+    }
+
+    pub fn deferrable(mut self, deferrable: Deferrable) -> Self {
+        self.deferrable = Some(deferrable);
+        self
+    }
+
+    /// Compare two foreign keys, treating unset options as the database defaults
+    /// (`NO ACTION`, `MATCH SIMPLE`). The referenced schema is only compared if both are set.
+    pub fn lossy_eq(&self, other: &ForeignKey) -> bool {
+        let action = |a: Option<ReferentialAction>| a.unwrap_or(ReferentialAction::NoAction);
+        let match_type = |m: Option<MatchType>| m.unwrap_or(MatchType::Simple);
+        let schema_eq = match (&self.schema, &other.schema) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        };
+        schema_eq
+            && self.table == other.table
+            && self.columns == other.columns
+            && action(self.on_delete) == action(other.on_delete)
+            && action(self.on_update) == action(other.on_update)
+            && match_type(self.match_type) == match_type(other.match_type)
+            && self.deferrable == other.deferrable
+    }
+}
+
+/// A table-level primary key. If `name` is not set, the database picks one, which for Postgres
+/// is `{table}_pkey`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrimaryKey {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+}
+
+impl PrimaryKey {
+    pub fn new(columns: &[&str]) -> Self {
+        PrimaryKey {
+            name: None,
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+}
+
+impl ToSql for PrimaryKey {
+    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+        if let Some(name) = &self.name {
+            buf.push_str("CONSTRAINT ");
+            buf.push_quoted(name);
+            buf.push(' ');
+        }
+        buf.push_str("PRIMARY KEY (");
+        buf.push_quoted_sequence(&self.columns, ", ");
+        buf.push(')');
+    }
+}
+
+/// One element of an exclusion constraint, e.g. `"during" WITH &&`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExcludeElement {
+    pub column: String,
+    pub operator: String,
+}
+
+/// A Postgres exclusion constraint, e.g. `EXCLUDE USING gist ("room" WITH =, "during" WITH &&)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Exclude {
+    /// The index method, e.g. `gist`.
+    pub using: String,
+    pub elements: Vec<ExcludeElement>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub where_: Option<Expr>,
+}
+
+impl Exclude {
+    pub fn new(using: &str) -> Self {
+        Exclude {
+            using: using.to_string(),
+            elements: vec![],
+            where_: None,
+        }
+    }
+
+    pub fn element(mut self, column: &str, operator: &str) -> Self {
+        self.elements.push(ExcludeElement {
+            column: column.to_string(),
+            operator: operator.to_string(),
+        });
+        self
+    }
+
+    pub fn where_(mut self, where_: Expr) -> Self {
+        self.where_ = Some(where_);
+        self
+    }
+}
+
+impl ToSql for Exclude {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("EXCLUDE USING ");
+        buf.push_str(&self.using);
+        buf.push_str(" (");
+        for (i, element) in self.elements.iter().enumerate() {
+            if i > 0 {
+                buf.push_str(", ");
+            }
+            buf.push_quoted(&element.column);
+            buf.push_str(" WITH ");
+            buf.push_str(&element.operator);
+        }
+        buf.push(')');
+        if let Some(where_) = &self.where_ {
+            buf.push_str(" WHERE (");
+            buf.push_sql(where_, dialect);
+            buf.push(')');
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,29 +225,145 @@ pub struct ForeignKey {
 #[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum Constraint {
     ForeignKey(ForeignKey),
+    Unique,
+    Check(Expr),
+    /// Only valid as a table-level constraint.
+    Exclude(Exclude),
 }
 
 impl Constraint {
     pub fn foreign_key(table: String, columns: Vec<String>) -> Self {
-        Constraint::ForeignKey(ForeignKey { table, columns })
+        Constraint::ForeignKey(ForeignKey {
+            schema: None,
+            table,
+            columns,
+            on_delete: None,
+            on_update: None,
+            match_type: None,
+            deferrable: None,
+        })
     }
 
+    pub fn check(expr: impl Into<Expr>) -> Self {
+        Constraint::Check(expr.into())
+    }
+
+    #[deprecated(note = "Returns the referenced table of a foreign key. Use `TableConstraint::name` for constraint names.")]
     pub fn name(&self) -> &str {
         match self {
             Constraint::ForeignKey(fk) => &fk.table,
+            _ => "",
         }
     }
+
+    /// The name sqlmo gives the constraint when it's declared on `column` and added with
+    /// `ALTER TABLE`. These match the names Postgres picks for constraints declared inline.
+    pub fn default_name(&self, table: &str, column: &str) -> String {
+        match self {
+            Constraint::ForeignKey(_) => format!("{table}_{column}_fkey"),
+            Constraint::Unique => format!("{table}_{column}_key"),
+            Constraint::Check(_) => format!("{table}_{column}_check"),
+            Constraint::Exclude(_) => format!("{table}_{column}_excl"),
+        }
+    }
+
+    pub fn lossy_eq(&self, other: &Constraint) -> bool {
+        use Constraint::*;
+        match (self, other) {
+            (ForeignKey(a), ForeignKey(b)) => a.lossy_eq(b),
+            (Unique, Unique) => true,
+            (Check(a), Check(b)) => a.lossy_eq(b),
+            (Exclude(a), Exclude(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Write the constraint as it appears in a table-level clause, e.g.
+    /// `FOREIGN KEY ("a", "b") REFERENCES ...`.
+    pub(crate) fn write_table_sql(&self, columns: &[String], buf: &mut String, dialect: Dialect) {
+        match self {
+            Constraint::ForeignKey(fk) => {
+                buf.push_str("FOREIGN KEY (");
+                buf.push_quoted_sequence(columns, ", ");
+                buf.push_str(") ");
+                buf.push_sql(fk, dialect);
+            }
+            Constraint::Unique => {
+                buf.push_str("UNIQUE (");
+                buf.push_quoted_sequence(columns, ", ");
+                buf.push(')');
+            }
+            Constraint::Check(_) | Constraint::Exclude(_) => self.write_sql(buf, dialect),
+        }
+    }
+}
+
+/// A named constraint over one or more columns of a table, e.g. a multi-column foreign key.
+/// `columns` is unused for `Check` and `Exclude` constraints.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableConstraint {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub constraint: Constraint,
+}
+
+impl TableConstraint {
+    pub fn new(name: &str, columns: &[&str], constraint: Constraint) -> Self {
+        TableConstraint {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            constraint,
+        }
+    }
+
+    /// Compare definitions, ignoring the name. Columns aren't compared for `Check` and `Exclude`.
+    pub fn lossy_eq(&self, other: &TableConstraint) -> bool {
+        let columns_eq = matches!(self.constraint, Constraint::Check(_) | Constraint::Exclude(_))
+            || self.columns == other.columns;
+        columns_eq && self.constraint.lossy_eq(&other.constraint)
+    }
+}
+
+impl ToSql for TableConstraint {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("CONSTRAINT ");
+        buf.push_quoted(&self.name);
+        buf.push(' ');
+        self.constraint.write_table_sql(&self.columns, buf, dialect);
+    }
 }
 
 impl ToSql for ForeignKey {
-    fn write_sql(&self, buf: &mut String, _dialect: Dialect) {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         buf.push_str("REFERENCES ");
-        buf.push_quoted(&self.table);
+        buf.push_table_name(&self.schema, &self.table);
         if !self.columns.is_empty() {
             buf.push('(');
             buf.push_quoted_sequence(&self.columns, ", ");
             buf.push(')');
         }
+        if let Some(match_type) = self.match_type {
+            buf.push_str(match match_type {
+                MatchType::Full => " MATCH FULL",
+                MatchType::Partial => " MATCH PARTIAL",
+                MatchType::Simple => " MATCH SIMPLE",
+            });
+        }
+        if let Some(action) = &self.on_delete {
+            buf.push_str(" ON DELETE ");
+            buf.push_sql(action, dialect);
+        }
+        if let Some(action) = &self.on_update {
+            buf.push_str(" ON UPDATE ");
+            buf.push_sql(action, dialect);
+        }
+        if let Some(deferrable) = self.deferrable {
+            buf.push_str(match deferrable {
+                Deferrable::InitiallyImmediate => " DEFERRABLE INITIALLY IMMEDIATE",
+                Deferrable::InitiallyDeferred => " DEFERRABLE INITIALLY DEFERRED",
+            });
+        }
     }
 }
 
@@ -43,6 +371,75 @@ impl ToSql for Constraint {
     fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         match self {
             Constraint::ForeignKey(fk) => fk.write_sql(buf, dialect),
+            Constraint::Unique => buf.push_str("UNIQUE"),
+            Constraint::Check(expr) => {
+                buf.push_str("CHECK (");
+                buf.push_sql(expr, dialect);
+                buf.push(')');
+            }
+            Constraint::Exclude(exclude) => exclude.write_sql(buf, dialect),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreign_key() {
+        let fk = ForeignKey::new("team", &["id"])
+            .schema("auth")
+            .match_type(MatchType::Full)
+            .on_delete(ReferentialAction::Cascade)
+            .on_update(ReferentialAction::SetNull)
+            .deferrable(Deferrable::InitiallyDeferred);
+        assert_eq!(
+            fk.to_sql(Dialect::Postgres),
+            r#"REFERENCES "auth"."team"("id") MATCH FULL ON DELETE CASCADE ON UPDATE SET NULL DEFERRABLE INITIALLY DEFERRED"#
+        );
+
+        let c = TableConstraint::new(
+            "fk_membership",
+            &["team_id", "user_id"],
+            Constraint::ForeignKey(ForeignKey::new("member", &["team_id", "user_id"])),
+        );
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"CONSTRAINT "fk_membership" FOREIGN KEY ("team_id", "user_id") REFERENCES "member"("team_id", "user_id")"#
+        );
+    }
+
+    #[test]
+    fn test_table_constraints() {
+        let c = TableConstraint::new("user_email_key", &["team_id", "email"], Constraint::Unique);
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"CONSTRAINT "user_email_key" UNIQUE ("team_id", "email")"#
+        );
+
+        let c = TableConstraint::new("price_positive", &[], Constraint::check("price > 0"));
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"CONSTRAINT "price_positive" CHECK (price > 0)"#
+        );
+
+        let exclude = Exclude::new("gist")
+            .element("room", "=")
+            .element("during", "&&")
+            .where_("NOT cancelled".into());
+        let c = TableConstraint::new("no_overlap", &[], Constraint::Exclude(exclude));
+        assert_eq!(
+            c.to_sql(Dialect::Postgres),
+            r#"CONSTRAINT "no_overlap" EXCLUDE USING gist ("room" WITH =, "during" WITH &&) WHERE (NOT cancelled)"#
+        );
+    }
+
+    #[test]
+    fn test_lossy_eq() {
+        let fk = ForeignKey::new("team", &["id"]);
+        assert!(fk.lossy_eq(&fk.clone().on_delete(ReferentialAction::NoAction).schema("public")));
+        assert!(!fk.lossy_eq(&fk.clone().on_delete(ReferentialAction::Cascade)));
+        assert!(Constraint::check("(price > 0)").lossy_eq(&Constraint::check("price > 0")));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::schema::Type;
+use crate::util::SqlExtension;
+use crate::Dialect;
+
+/// How the data in a column survives an `ALTER COLUMN ... TYPE`, following Postgres casts.
+/// Variants are ordered from safest to least safe.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Conversion {
+    /// The stored values don't change, e.g. `varchar(64)` to `text`.
+    BinaryCompatible,
+    /// Every value converts without loss, e.g. `integer` to `bigint`.
+    Widening,
+    /// Values can fail to convert or lose information, e.g. `text` to `integer`.
+    Lossy,
+    /// There is no cast between the types, e.g. `boolean` to `date`.
+    Impossible,
+}
+
+impl Conversion {
+    /// Whether existing data might be lost or rejected by the conversion.
+    pub fn is_risky(&self) -> bool {
+        *self >= Conversion::Lossy
+    }
+}
+
+fn int_rank(typ: &Type) -> Option<u8> {
+    use Type::*;
+    match typ {
+        I16 => Some(1),
+        I32 | Serial => Some(2),
+        I64 | BigSerial => Some(3),
+        _ => None,
+    }
+}
+
+/// Number of decimal digits an integer type needs.
+fn int_digits(rank: u8) -> u8 {
+    match rank {
+        1 => 5,
+        2 => 10,
+        _ => 19,
+    }
+}
+
+fn is_text(typ: &Type) -> bool {
+    matches!(typ, Type::Text | Type::Citext | Type::Varchar(_) | Type::Char(_))
+}
+
+fn is_timestamp(typ: &Type) -> bool {
+    use Type::*;
+    matches!(typ, DateTime | NaiveDateTime | Timestamp(_) | TimestampTz(_))
+}
+
+fn timestamp_precision(typ: &Type) -> u8 {
+    match typ {
+        Type::Timestamp(p) | Type::TimestampTz(p) => *p,
+        _ => 6,
+    }
+}
+
+fn has_tz(typ: &Type) -> bool {
+    matches!(typ, Type::DateTime | Type::TimestampTz(_))
+}
+
+fn widen_if(condition: bool) -> Conversion {
+    if condition {
+        Conversion::Widening
+    } else {
+        Conversion::Lossy
+    }
+}
+
+impl Type {
+    /// Classify converting a column of this type to `to`.
+    pub fn conversion_to(&self, to: &Type) -> Conversion {
+        use Conversion::*;
+        use Type::*;
+        if self.lossy_eq(to) {
+            return BinaryCompatible;
+        }
+        if let (Some(from), Some(to)) = (int_rank(self), int_rank(to)) {
+            return widen_if(from < to);
+        }
+        match (self, to) {
+            (Other(_), _) | (_, Other(_)) => Lossy,
+            // text types
+            (Varchar(_) | Text | Citext, Text | Citext) => BinaryCompatible,
+            (Varchar(n), Varchar(m)) if m >= n => BinaryCompatible,
+            (Char(_), Text | Citext) => Widening,
+            (Char(n), Varchar(m) | Char(m)) => widen_if(m >= n),
+            (a, Varchar(_) | Char(_)) if is_text(a) => Lossy,
+            // everything has a text representation, and text can be parsed as anything
+            (_, Text | Citext) => Widening,
+            (a, _) if is_text(a) => Lossy,
+            (_, Varchar(_) | Char(_)) => Lossy,
+            // numbers
+            (I16, F32)
+            | (I16 | I32 | Serial, F64)
+            | (I16 | I32 | Serial | I64 | BigSerial, Decimal) => Widening,
+            (a, Numeric(p, s)) if int_rank(a).is_some() => {
+                widen_if(p.saturating_sub(*s) >= int_digits(int_rank(a).unwrap()))
+            }
+            (a, F32 | F64) if int_rank(a).is_some() => Lossy,
+            (F32, F64) | (Numeric(_, _), Decimal) => Widening,
+            (Numeric(p, s), Numeric(p2, s2)) => {
+                widen_if(s2 >= s && p2.saturating_sub(*s2) >= p.saturating_sub(*s))
+            }
+            (F32 | F64 | Decimal | Numeric(_, _) | Money, b)
+                if int_rank(b).is_some()
+                    || matches!(b, F32 | F64 | Decimal | Numeric(_, _) | Money) =>
+            {
+                Lossy
+            }
+            (a, Money) if int_rank(a).is_some() => Lossy,
+            (a, Boolean) if int_rank(a).is_some() => Lossy,
+            (Boolean, b) if int_rank(b).is_some() => Widening,
+            // dates and times
+            (Date, b) if is_timestamp(b) => Widening,
+            (a, b) if is_timestamp(a) && is_timestamp(b) => {
+                let precision = timestamp_precision(b) >= timestamp_precision(a);
+                widen_if(precision && has_tz(a) == has_tz(b))
+            }
+            (a, Date | Time) if is_timestamp(a) => Lossy,
+            (a, b) if is_timestamp(a) && int_rank(b) == Some(3) => Lossy,
+            (a, b) if int_rank(a).is_some() && has_tz(b) => Lossy,
+            (Time, TimeTz | Duration | Interval(_)) => Widening,
+            (TimeTz, Time) | (Duration | Interval(_), Time) => Lossy,
+            (Duration, Interval(_)) | (Interval(_), Duration | Interval(_)) => Lossy,
+            // other types
+            (Json, Jsonb) | (Jsonb, Json) => Widening,
+            (Cidr, Inet) => Widening,
+            (Inet, Cidr) => Lossy,
+            (Bit(n), Bit(m)) => widen_if(m == n),
+            (Enum(_), Enum(_)) => Lossy,
+            (Array(a), Array(b)) => a.conversion_to(b),
+            _ => Impossible,
+        }
+    }
+
+    /// The `USING` expression for converting `column` to `to`, for conversions where a plain
+    /// cast doesn't exist in Postgres.
+    pub fn conversion_using(&self, column: &str, to: &Type) -> Option<String> {
+        use Type::*;
+        let mut buf = String::new();
+        match (self, to) {
+            (Boolean, b) if int_rank(b).is_some() => {
+                buf.push_str("CASE WHEN ");
+                buf.push_quoted(column);
+                buf.push_str(" THEN 1 ELSE 0 END");
+            }
+            (a, Boolean) if int_rank(a).is_some() => {
+                buf.push_quoted(column);
+                buf.push_str(" <> 0");
+            }
+            (a, b) if is_timestamp(a) && int_rank(b) == Some(3) => {
+                buf.push_str("extract(epoch from ");
+                buf.push_quoted(column);
+                buf.push_str(")::bigint");
+            }
+            (a, b) if int_rank(a).is_some() && has_tz(b) => {
+                buf.push_str("to_timestamp(");
+                buf.push_quoted(column);
+                buf.push(')');
+            }
+            // enums only cast to and from text
+            (Enum(_), Enum(_)) => {
+                buf.push_quoted(column);
+                buf.push_str("::text::");
+                buf.push_sql(to, Dialect::Postgres);
+            }
+            _ => return None,
+        }
+        Some(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Type::*;
+
+    #[test]
+    fn test_conversion() {
+        assert_eq!(Varchar(64).conversion_to(&Text), Conversion::BinaryCompatible);
+        assert_eq!(Varchar(64).conversion_to(&Varchar(32)), Conversion::Lossy);
+        assert_eq!(I32.conversion_to(&I64), Conversion::Widening);
+        assert_eq!(I64.conversion_to(&I32), Conversion::Lossy);
+        assert_eq!(Text.conversion_to(&I32), Conversion::Lossy);
+        assert_eq!(I32.conversion_to(&Text), Conversion::Widening);
+        assert_eq!(I32.conversion_to(&Numeric(5, 2)), Conversion::Lossy);
+        assert_eq!(I32.conversion_to(&Numeric(12, 2)), Conversion::Widening);
+        assert_eq!(Date.conversion_to(&DateTime), Conversion::Widening);
+        assert_eq!(Boolean.conversion_to(&Date), Conversion::Impossible);
+        assert_eq!(Uuid.conversion_to(&I32), Conversion::Impossible);
+        assert!(!Json.conversion_to(&Jsonb).is_risky());
+    }
+
+    #[test]
+    fn test_conversion_using() {
+        assert_eq!(
+            Boolean.conversion_using("active", &I16).unwrap(),
+            r#"CASE WHEN "active" THEN 1 ELSE 0 END"#
+        );
+        assert_eq!(
+            Enum("mood".to_string())
+                .conversion_using("mood", &Enum("feeling".to_string()))
+                .unwrap(),
+            r#""mood"::text::"feeling""#
+        );
+        assert_eq!(I32.conversion_using("id", &I64), None);
+    }
+}
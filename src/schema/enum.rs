@@ -0,0 +1,55 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, ToSql};
+
+/// A Postgres enum type. Columns refer to it with `Type::Enum(name)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Enum {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub schema: Option<String>,
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+impl Enum {
+    pub fn new(name: &str, variants: &[&str]) -> Self {
+        Enum {
+            schema: None,
+            name: name.to_string(),
+            variants: variants.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    /// Write the type of a `column` of this enum for dialects without enum types: MySQL declares
+    /// the variants inline, and Sqlite uses a `CHECK` constraint.
+    pub(crate) fn write_inline_type(&self, buf: &mut String, dialect: Dialect, column: &str) {
+        match dialect {
+            Dialect::Mysql => {
+                buf.push_str("ENUM(");
+                buf.push_literal_sequence(&self.variants, ", ", dialect);
+                buf.push(')');
+            }
+            Dialect::Sqlite => {
+                buf.push_str("TEXT CHECK (");
+                buf.push_quoted(column);
+                buf.push_str(" IN (");
+                buf.push_literal_sequence(&self.variants, ", ", dialect);
+                buf.push_str("))");
+            }
+            Dialect::Postgres => buf.push_table_name(&self.schema, &self.name),
+        }
+    }
+}
+
+impl ToSql for Enum {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        buf.push_str("ENUM (");
+        buf.push_literal_sequence(&self.variants, ", ", dialect);
+        buf.push(')');
+    }
+}
@@ -0,0 +1,147 @@
+use crate::util::SqlExtension;
+use crate::{Dialect, Type};
+
+/// The column a sequence belongs to. The sequence is dropped along with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedBy {
+    pub table: String,
+    pub column: String,
+}
+
+/// Options shared by `CREATE SEQUENCE` and `ALTER SEQUENCE`. Options that aren't set take the
+/// database default, and aren't compared by `migrate()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SequenceOptions {
+    /// One of the integer types.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub typ: Option<Type>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub start: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub increment: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub min_value: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub max_value: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub cycle: Option<bool>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub owned_by: Option<OwnedBy>,
+}
+
+impl SequenceOptions {
+    /// The options set in `desired` that differ from `self`.
+    pub fn changes(&self, desired: &SequenceOptions) -> SequenceOptions {
+        fn changed<T: Clone + PartialEq>(current: &Option<T>, desired: &Option<T>) -> Option<T> {
+            desired.clone().filter(|d| current.as_ref() != Some(d))
+        }
+        SequenceOptions {
+            typ: changed(&self.typ, &desired.typ),
+            start: changed(&self.start, &desired.start),
+            increment: changed(&self.increment, &desired.increment),
+            min_value: changed(&self.min_value, &desired.min_value),
+            max_value: changed(&self.max_value, &desired.max_value),
+            cycle: changed(&self.cycle, &desired.cycle),
+            owned_by: changed(&self.owned_by, &desired.owned_by),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &SequenceOptions::default()
+    }
+}
+
+impl SequenceOptions {
+    /// Write the options of a sequence in `schema`. Postgres keeps a sequence in the schema of the
+    /// table that owns it, so the table is qualified with the same schema.
+    pub(crate) fn write_sql(&self, buf: &mut String, dialect: Dialect, schema: &Option<String>) {
+        if let Some(typ) = &self.typ {
+            buf.push_str(" AS ");
+            buf.push_sql(typ, dialect);
+        }
+        if let Some(increment) = self.increment {
+            buf.push_str(&format!(" INCREMENT BY {}", increment));
+        }
+        if let Some(min_value) = self.min_value {
+            buf.push_str(&format!(" MINVALUE {}", min_value));
+        }
+        if let Some(max_value) = self.max_value {
+            buf.push_str(&format!(" MAXVALUE {}", max_value));
+        }
+        if let Some(start) = self.start {
+            buf.push_str(&format!(" START WITH {}", start));
+        }
+        match self.cycle {
+            Some(true) => buf.push_str(" CYCLE"),
+            Some(false) => buf.push_str(" NO CYCLE"),
+            None => {}
+        }
+        if let Some(owned_by) = &self.owned_by {
+            buf.push_str(" OWNED BY ");
+            buf.push_table_name(schema, &owned_by.table);
+            buf.push('.');
+            buf.push_quoted(&owned_by.column);
+        }
+    }
+}
+
+/// A standalone Postgres sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sequence {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub schema: Option<String>,
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: SequenceOptions,
+}
+
+impl Sequence {
+    pub fn new(name: &str) -> Self {
+        Sequence {
+            schema: None,
+            name: name.to_string(),
+            options: SequenceOptions::default(),
+        }
+    }
+
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    pub fn start(mut self, start: i64) -> Self {
+        self.options.start = Some(start);
+        self
+    }
+
+    pub fn increment(mut self, increment: i64) -> Self {
+        self.options.increment = Some(increment);
+        self
+    }
+
+    pub fn min_value(mut self, min_value: i64) -> Self {
+        self.options.min_value = Some(min_value);
+        self
+    }
+
+    pub fn max_value(mut self, max_value: i64) -> Self {
+        self.options.max_value = Some(max_value);
+        self
+    }
+
+    pub fn cycle(mut self, cycle: bool) -> Self {
+        self.options.cycle = Some(cycle);
+        self
+    }
+
+    pub fn owned_by(mut self, table: &str, column: &str) -> Self {
+        self.options.owned_by = Some(OwnedBy {
+            table: table.to_string(),
+            column: column.to_string(),
+        });
+        self
+    }
+}
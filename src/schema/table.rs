@@ -1,5 +1,6 @@
 use crate::schema::index::Index;
 use crate::schema::column::Column;
+use crate::schema::constraint::{PrimaryKey, TableConstraint};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -8,11 +9,44 @@ pub struct Table {
     pub name: String,
     pub columns: Vec<Column>,
     pub indexes: Vec<Index>,
+    /// Table-level primary key. Takes precedence over `Column::primary_key`, and is required
+    /// to name the constraint.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub primary_key: Option<PrimaryKey>,
+    /// Named constraints that span one or more columns.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub constraints: Vec<TableConstraint>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub comment: Option<String>,
 }
 
 impl Table {
+    /// The first column of the primary key.
     pub fn primary_key(&self) -> Option<&Column> {
-        self.columns.iter().find(|c| c.primary_key)
+        let name = self.primary_key_columns().into_iter().next()?;
+        self.columns.iter().find(|c| c.name == name)
+    }
+
+    /// The primary key columns, from the table-level primary key if set, otherwise from the
+    /// columns marked `primary_key`.
+    pub fn primary_key_columns(&self) -> Vec<String> {
+        match &self.primary_key {
+            Some(pk) => pk.columns.clone(),
+            None => self
+                .columns
+                .iter()
+                .filter(|c| c.primary_key)
+                .map(|c| c.name.clone())
+                .collect(),
+        }
+    }
+
+    /// The primary key constraint name, falling back to the Postgres default.
+    pub fn primary_key_name(&self) -> String {
+        self.primary_key
+            .as_ref()
+            .and_then(|pk| pk.name.clone())
+            .unwrap_or_else(|| format!("{}_pkey", self.name))
     }
 
     pub fn new(name: &str) -> Table {
@@ -21,6 +55,9 @@ impl Table {
             name: name.to_string(),
             columns: vec![],
             indexes: vec![],
+            primary_key: None,
+            constraints: vec![],
+            comment: None,
         }
     }
 
@@ -34,8 +71,18 @@ impl Table {
         self
     }
 
+    pub fn constraint(mut self, constraint: TableConstraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
     pub fn schema(mut self, schema: &str) -> Self {
         self.schema = Some(schema.to_string());
         self
     }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
 }
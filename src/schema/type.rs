@@ -2,6 +2,7 @@ use crate::to_sql::{Dialect, ToSql};
 use anyhow::Result;
 use std::str::FromStr;
 
+use crate::schema::Enum;
 use crate::util::SqlExtension;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,40 +13,310 @@ pub enum Type {
     I16,
     I32,
     I64,
+    // auto-incrementing integer types. These are the same as `I32` and `I64` once created.
+    Serial,
+    BigSerial,
     // float types
     F32,
     F64,
     // arbitrary precision types
     Decimal,
     Numeric(u8, u8),
+    Money,
     // byte types
     Bytes,
     // date types
     Time,
+    /// `time with time zone`
+    TimeTz,
     Date,
     DateTime,
     NaiveDateTime,
+    /// `timestamp(p) without time zone`
+    Timestamp(u8),
+    /// `timestamp(p) with time zone`
+    TimestampTz(u8),
     Duration,
+    /// An interval restricted to the given fields, e.g. `day to second`.
+    Interval(String),
     // json types
     Json,
     Jsonb,
     // extension types
     Uuid,
+    Citext,
+    Hstore,
+    // network address types
+    Inet,
+    Cidr,
+    MacAddr,
+    // other builtin types
+    Xml,
+    TsVector,
+    Point,
     // string types
     Text,
+    Varchar(u32),
+    Char(u32),
+    // bit string types
+    Bit(u32),
     // Array types
     Array(Box<Type>),
+    /// A builtin range type, e.g. `Range(I32)` is `int4range`.
+    Range(Box<Type>),
+    /// A user-defined enum, see `schema::Enum`.
+    Enum(String),
     Other(String),
 }
 
 impl Type {
+    /// The name of the enum type, if this is an enum or an array of one.
+    pub fn enum_name(&self) -> Option<&str> {
+        match self {
+            Type::Enum(name) => Some(name),
+            Type::Array(inner) => inner.enum_name(),
+            _ => None,
+        }
+    }
+
+    /// Write the type, naming enums as they're defined in `enums`, with their schema.
+    pub(crate) fn write_sql_with_enums(&self, buf: &mut String, dialect: Dialect, enums: &[Enum]) {
+        match self {
+            Type::Enum(name) => match enums.iter().find(|e| &e.name == name) {
+                Some(e) => buf.push_table_name(&e.schema, &e.name),
+                None => buf.push_quoted(name),
+            },
+            Type::Array(inner) if dialect == Dialect::Postgres => {
+                inner.write_sql_with_enums(buf, dialect, enums);
+                buf.push_str("[]");
+            }
+            _ => buf.push_sql(self, dialect),
+        }
+    }
+
     pub fn lossy_eq(&self, other: &Type) -> bool {
         use Type::*;
         match (self, other) {
             (Other(_), _) => true,
+            (Serial, I32) | (I32, Serial) | (BigSerial, I64) | (I64, BigSerial) => true,
+            // 6 is the default precision in Postgres
+            (DateTime, TimestampTz(6)) | (TimestampTz(6), DateTime) => true,
+            (NaiveDateTime, Timestamp(6)) | (Timestamp(6), NaiveDateTime) => true,
             (a, b) => a == b,
         }
     }
+
+    /// Like `lossy_eq`, but also true when `dialect` declares both types the same way, since the
+    /// types read back from it can't tell them apart, e.g. MySQL `DATETIME(6)` is both `DateTime`
+    /// and `NaiveDateTime`.
+    pub fn lossy_eq_in(&self, other: &Type, dialect: Dialect) -> bool {
+        self.lossy_eq(other)
+            || (dialect != Dialect::Postgres && self.to_sql(dialect) == other.to_sql(dialect))
+    }
+
+    /// Parse a type name as reported by the given dialect. For Postgres, this is the same as
+    /// `FromStr`. Types that a dialect doesn't distinguish parse to a single variant, e.g. MySQL
+    /// `JSON` is always `Type::Json`.
+    pub fn parse(s: &str, dialect: Dialect) -> Result<Type> {
+        match dialect {
+            Dialect::Postgres => Type::from_str(s),
+            Dialect::Mysql => Ok(parse_mysql(s)),
+            Dialect::Sqlite => Ok(parse_sqlite(s)),
+        }
+    }
+
+    fn write_mysql(&self, buf: &mut String) {
+        use Type::*;
+        let s = match self {
+            Boolean => "BOOLEAN",
+            I16 => "SMALLINT",
+            I32 => "INT",
+            I64 => "BIGINT",
+            Serial => "INT AUTO_INCREMENT",
+            BigSerial => "BIGINT AUTO_INCREMENT",
+            F32 => "FLOAT",
+            F64 => "DOUBLE",
+            Decimal => "DECIMAL(65, 30)",
+            Money => "DECIMAL(19, 4)",
+            Numeric(p, s) => {
+                return buf.push_str(&format!("DECIMAL({}, {})", p, s));
+            }
+            Bytes => "LONGBLOB",
+            Time | TimeTz | Duration | Interval(_) => "TIME(6)",
+            Date => "DATE",
+            DateTime | NaiveDateTime => "DATETIME(6)",
+            Timestamp(p) | TimestampTz(p) => {
+                return buf.push_str(&format!("DATETIME({})", p.min(&6)));
+            }
+            Json | Jsonb | Array(_) | Range(_) | Hstore => "JSON",
+            Uuid => "BINARY(16)",
+            Inet | Cidr => "VARCHAR(43)",
+            MacAddr => "VARCHAR(17)",
+            Point => "POINT",
+            Text | Citext | Xml | TsVector => "TEXT",
+            Varchar(n) => {
+                return buf.push_str(&format!("VARCHAR({})", n));
+            }
+            Char(n) => {
+                return buf.push_str(&format!("CHAR({})", n));
+            }
+            Bit(n) => {
+                return buf.push_str(&format!("BIT({})", n));
+            }
+            Enum(_) | Other(_) => unreachable!(),
+        };
+        buf.push_str(s);
+    }
+
+    fn write_sqlite(&self, buf: &mut String) {
+        use Type::*;
+        // Sqlite keeps the declared name, and derives the column affinity from it.
+        let s = match self {
+            Boolean => "BOOLEAN",
+            I16 => "SMALLINT",
+            I32 => "INT",
+            I64 | Serial | BigSerial => "INTEGER",
+            F32 => "FLOAT",
+            F64 => "DOUBLE",
+            Decimal => "NUMERIC",
+            Money => "NUMERIC(19, 4)",
+            Numeric(p, s) => {
+                return buf.push_str(&format!("NUMERIC({}, {})", p, s));
+            }
+            Bytes => "BLOB",
+            Time | TimeTz => "TIME",
+            Date => "DATE",
+            DateTime => "DATETIME",
+            NaiveDateTime => "TIMESTAMP",
+            TimestampTz(p) => {
+                return buf.push_str(&format!("DATETIME({})", p));
+            }
+            Timestamp(p) => {
+                return buf.push_str(&format!("TIMESTAMP({})", p));
+            }
+            Duration | Interval(_) => "INTERVAL",
+            Json | Array(_) | Hstore => "JSON",
+            Jsonb => "JSONB",
+            Uuid => "UUID",
+            Text | Citext | Inet | Cidr | MacAddr | Xml | TsVector | Point | Range(_) => "TEXT",
+            Varchar(n) => {
+                return buf.push_str(&format!("VARCHAR({})", n));
+            }
+            Char(n) => {
+                return buf.push_str(&format!("CHAR({})", n));
+            }
+            Bit(n) => {
+                return buf.push_str(&format!("BIT({})", n));
+            }
+            Enum(_) | Other(_) => unreachable!(),
+        };
+        buf.push_str(s);
+    }
+}
+
+/// Split a type like `decimal(10,2) unsigned` into `("decimal", [10, 2])`.
+fn split_type(s: &str) -> (String, Vec<u32>) {
+    let s = s.trim().to_lowercase();
+    let Some((base, rest)) = s.split_once('(') else {
+        let base = s.split_whitespace().next().unwrap_or("").to_string();
+        return (base, vec![]);
+    };
+    let args = rest
+        .split(')')
+        .next()
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|a| a.trim().parse().ok())
+        .collect();
+    (base.trim().to_string(), args)
+}
+
+fn parse_mysql(s: &str) -> Type {
+    use Type::*;
+    let (base, args) = split_type(s);
+    match (base.as_str(), args.as_slice()) {
+        ("tinyint", [1]) | ("bool", _) | ("boolean", _) => Boolean,
+        ("tinyint", _) | ("smallint", _) => I16,
+        ("mediumint", _) | ("int", _) | ("integer", _) => I32,
+        ("bigint", _) => I64,
+        ("float", _) => F32,
+        ("double", _) | ("real", _) => F64,
+        ("decimal" | "numeric", [65, 30]) | ("decimal" | "numeric", []) => Decimal,
+        ("decimal" | "numeric", [p, s]) => Numeric(*p as u8, *s as u8),
+        ("binary", [16]) => Uuid,
+        ("varchar", [n]) => Varchar(*n),
+        ("char", [n]) => Char(*n),
+        ("bit", [n]) => Bit(*n),
+        ("bit", []) => Bit(1),
+        ("blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary", _) => Bytes,
+        ("time", _) => Time,
+        ("date", _) => Date,
+        ("datetime", [p]) if *p != 6 => Timestamp(*p as u8),
+        ("datetime", _) => NaiveDateTime,
+        ("timestamp", _) => DateTime,
+        ("json", _) => Json,
+        ("char" | "varchar" | "text" | "tinytext" | "mediumtext" | "longtext", _) => Text,
+        _ => Other(s.to_string()),
+    }
+}
+
+fn parse_sqlite(s: &str) -> Type {
+    use Type::*;
+    let (base, args) = split_type(s);
+    let name = s.to_lowercase();
+    match (base.as_str(), args.as_slice()) {
+        ("boolean" | "bool", _) => Boolean,
+        ("smallint" | "tinyint", _) => I16,
+        ("int" | "mediumint", _) => I32,
+        ("integer" | "bigint", _) => I64,
+        ("float", _) => F32,
+        ("double" | "real", _) => F64,
+        ("numeric" | "decimal", [p, s]) => Numeric(*p as u8, *s as u8),
+        ("numeric" | "decimal", _) => Decimal,
+        ("blob", _) => Bytes,
+        ("time", _) => Time,
+        ("date", _) => Date,
+        ("datetime", [p]) => TimestampTz(*p as u8),
+        ("datetime", _) => DateTime,
+        ("timestamp", [p]) => Timestamp(*p as u8),
+        ("timestamp", _) => NaiveDateTime,
+        ("interval", _) => Duration,
+        ("json", _) => Json,
+        ("jsonb", _) => Jsonb,
+        ("uuid", _) => Uuid,
+        ("varchar", [n]) => Varchar(*n),
+        ("char", [n]) => Char(*n),
+        ("bit", [n]) => Bit(*n),
+        ("text" | "varchar" | "char" | "clob", _) => Text,
+        // the Sqlite affinity rules, for any other declared type
+        _ if name.contains("int") => I64,
+        _ if name.contains("char") || name.contains("clob") || name.contains("text") => Text,
+        _ if name.contains("blob") || name.trim().is_empty() => Bytes,
+        _ if name.contains("real") || name.contains("floa") || name.contains("doub") => F64,
+        _ => Other(s.to_string()),
+    }
+}
+
+/// Parse the Postgres forms that carry a modifier, e.g. `character varying(64)`,
+/// `timestamp(3) with time zone` or `interval day to second`.
+fn parse_postgres_modifier(s: &str) -> Option<Type> {
+    use Type::*;
+    let s = s.trim().to_lowercase();
+    if let Some(fields) = s.strip_prefix("interval ") {
+        return Some(Interval(fields.trim().to_string()));
+    }
+    let (base, rest) = s.split_once('(')?;
+    let (arg, suffix) = rest.split_once(')')?;
+    let n: u32 = arg.trim().parse().ok()?;
+    let typ = match (base.trim(), suffix.trim()) {
+        ("character varying" | "varchar", "") => Varchar(n),
+        ("character" | "char" | "bpchar", "") => Char(n),
+        ("bit", "") => Bit(n),
+        ("timestamp", "" | "without time zone") => Timestamp(n as u8),
+        ("timestamp", "with time zone") | ("timestamptz", "") => TimestampTz(n as u8),
+        _ => return None,
+    };
+    Some(typ)
 }
 
 impl FromStr for Type {
@@ -53,6 +324,9 @@ impl FromStr for Type {
 
     fn from_str(s: &str) -> Result<Self> {
         use Type::*;
+        if let Some(typ) = parse_postgres_modifier(s) {
+            return Ok(typ);
+        }
         let s = match s {
             "numeric" => Decimal,
             "bigint" => I64,
@@ -65,6 +339,8 @@ impl FromStr for Type {
             "bytea" => Bytes,
             "timestamp with time zone" => DateTime,
             "timestamp without time zone" => NaiveDateTime,
+            "timestamptz" => DateTime,
+            "timestamp" => NaiveDateTime,
             "interval" => Duration,
             "json" => Json,
             "jsonb" => Jsonb,
@@ -73,7 +349,28 @@ impl FromStr for Type {
             "text" => Text,
             "character varying" => Text,
             "varchar" => Text,
+            "character" => Char(1),
+            "bit" => Bit(1),
             "integer" => I32,
+            "serial" | "serial4" => Serial,
+            "bigserial" | "serial8" => BigSerial,
+            "money" => Money,
+            "time" | "time without time zone" => Time,
+            "timetz" | "time with time zone" => TimeTz,
+            "citext" => Citext,
+            "hstore" => Hstore,
+            "inet" => Inet,
+            "cidr" => Cidr,
+            "macaddr" => MacAddr,
+            "xml" => Xml,
+            "tsvector" => TsVector,
+            "point" => Point,
+            "int4range" => Range(Box::new(I32)),
+            "int8range" => Range(Box::new(I64)),
+            "numrange" => Range(Box::new(Decimal)),
+            "tsrange" => Range(Box::new(NaiveDateTime)),
+            "tstzrange" => Range(Box::new(DateTime)),
+            "daterange" => Range(Box::new(Date)),
             "ARRAY" => panic!("Encountered `ARRAY` type when reading data schema from database. ARRAY must be handled separately."),
             s => Other(s.to_string()),
         };
@@ -84,17 +381,35 @@ impl FromStr for Type {
 impl ToSql for Type {
     fn write_sql(&self, buf: &mut String, dialect: Dialect) {
         use self::Type::*;
+        match (self, dialect) {
+            (Enum(_) | Other(_), _) | (_, Dialect::Postgres) => {}
+            (_, Dialect::Mysql) => return self.write_mysql(buf),
+            (_, Dialect::Sqlite) => return self.write_sqlite(buf),
+        }
         let s = match self {
             Boolean => "boolean",
             I16 => "smallint",
             I32 => "integer",
             I64 => "bigint",
+            Serial => "serial",
+            BigSerial => "bigserial",
+            Money => "money",
             Bytes => "bytea",
             Time => "time without time zone",
+            TimeTz => "time with time zone",
             Date => "date",
             DateTime => "timestamptz",
             NaiveDateTime => "timestamp without time zone",
+            Timestamp(p) => {
+                return buf.push_str(&format!("timestamp({}) without time zone", p));
+            }
+            TimestampTz(p) => {
+                return buf.push_str(&format!("timestamp({}) with time zone", p));
+            }
             Duration => "interval",
+            Interval(fields) => {
+                return buf.push_str(&format!("interval {}", fields));
+            }
             Json => "json",
             Jsonb => "jsonb",
             F32 => "real",
@@ -104,16 +419,48 @@ impl ToSql for Type {
                 return buf.push_str(&format!("numeric({}, {})", p, s));
             }
             Uuid => "uuid",
+            Citext => "citext",
+            Hstore => "hstore",
+            Inet => "inet",
+            Cidr => "cidr",
+            MacAddr => "macaddr",
+            Xml => "xml",
+            TsVector => "tsvector",
+            Point => "point",
+            Range(inner) => match inner.as_ref() {
+                I32 => "int4range",
+                I64 => "int8range",
+                Decimal => "numrange",
+                NaiveDateTime => "tsrange",
+                DateTime => "tstzrange",
+                Date => "daterange",
+                z => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?z, "Unsupported range type. SQL may not be valid.");
+                    buf.push_str("/* Unsupported range type: ");
+                    buf.push_sql(z, dialect);
+                    buf.push_str(" */");
+                    return;
+                }
+            },
             Text => "character varying",
+            Varchar(n) => {
+                return buf.push_str(&format!("character varying({})", n));
+            }
+            Char(n) => {
+                return buf.push_str(&format!("character({})", n));
+            }
+            Bit(n) => {
+                return buf.push_str(&format!("bit({})", n));
+            }
             Array(inner) => {
                 buf.push_sql(inner.as_ref(), dialect);
-                if dialect == Dialect::Postgres {
-                    buf.push_str("[]");
-                } else {
-                    buf.push_str(" ARRAY")
-                }
+                buf.push_str("[]");
                 return;
             }
+            Enum(name) => {
+                return buf.push_quoted(name);
+            }
             Other(z) => {
                 #[cfg(feature = "tracing")]
                 tracing::warn!(z, "Unknown type. SQL may not be valid.");
@@ -137,4 +484,101 @@ mod test {
         let t = Type::from_str(s).unwrap();
         assert_eq!(t, Type::Decimal);
     }
+
+    #[test]
+    fn test_modifiers() {
+        use Type::*;
+        for typ in [
+            Varchar(64),
+            Char(2),
+            Bit(8),
+            Timestamp(3),
+            TimestampTz(0),
+            Interval("day to second".to_string()),
+        ] {
+            let sql = typ.to_sql(Dialect::Postgres);
+            assert_eq!(Type::from_str(&sql).unwrap(), typ, "{}", sql);
+        }
+        assert_eq!(Type::from_str("varchar(64)").unwrap(), Varchar(64));
+        assert_eq!(Type::from_str("character").unwrap(), Char(1));
+        assert!(TimestampTz(6).lossy_eq(&DateTime));
+        assert!(!Varchar(64).lossy_eq(&Varchar(128)));
+    }
+
+    #[test]
+    fn test_postgres_types() {
+        use Type::*;
+        for typ in [
+            Serial,
+            BigSerial,
+            Money,
+            Time,
+            TimeTz,
+            Citext,
+            Hstore,
+            Inet,
+            Cidr,
+            MacAddr,
+            Xml,
+            TsVector,
+            Point,
+            Range(Box::new(I64)),
+            Range(Box::new(DateTime)),
+        ] {
+            let sql = typ.to_sql(Dialect::Postgres);
+            assert_eq!(Type::from_str(&sql).unwrap(), typ, "{}", sql);
+        }
+        assert_eq!(Type::from_str("time").unwrap(), Time);
+        assert!(Serial.lossy_eq(&I32));
+        assert!(I64.lossy_eq(&BigSerial));
+        assert!(!Serial.lossy_eq(&I64));
+    }
+
+    #[test]
+    fn test_dialects() {
+        use Type::*;
+        let types = [
+            Boolean,
+            I16,
+            I32,
+            I64,
+            F32,
+            F64,
+            Decimal,
+            Numeric(10, 2),
+            Bytes,
+            Time,
+            Date,
+            DateTime,
+            NaiveDateTime,
+            Json,
+            Uuid,
+            Text,
+            Varchar(64),
+            Char(2),
+            Char(36),
+            Timestamp(3),
+            TimestampTz(3),
+            Bit(8),
+        ];
+        for dialect in [Dialect::Mysql, Dialect::Sqlite] {
+            for typ in &types {
+                let sql = typ.to_sql(dialect);
+                let parsed = Type::parse(&sql, dialect).unwrap();
+                assert!(parsed.lossy_eq_in(typ, dialect), "{}", sql);
+            }
+        }
+        // MySQL doesn't keep time zones, so these read back as their naive forms
+        assert_eq!(Type::parse("DATETIME(6)", Dialect::Mysql).unwrap(), NaiveDateTime);
+        assert_eq!(Type::parse("DATETIME(3)", Dialect::Mysql).unwrap(), Timestamp(3));
+        assert!(!DateTime.lossy_eq_in(&NaiveDateTime, Dialect::Postgres));
+        assert_eq!(Uuid.to_sql(Dialect::Mysql), "BINARY(16)");
+        assert_eq!(Type::parse("binary(16)", Dialect::Mysql).unwrap(), Uuid);
+        assert_eq!(Type::parse("char(36)", Dialect::Mysql).unwrap(), Char(36));
+        assert_eq!(Type::parse("int(11)", Dialect::Mysql).unwrap(), I32);
+        assert_eq!(Array(Box::new(I32)).to_sql(Dialect::Mysql), "JSON");
+        assert_eq!(DateTime.to_sql(Dialect::Sqlite), "DATETIME");
+        assert_eq!(Type::parse("VARYING CHARACTER(10)", Dialect::Sqlite).unwrap(), Text);
+        assert_eq!(Type::parse("UNSIGNED BIG INT", Dialect::Sqlite).unwrap(), I64);
+    }
 }
@@ -0,0 +1,175 @@
+use crate::query::{Expr, JoinTable};
+use crate::{Dialect, Select, ToSql};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::large_enum_variant)]
+pub enum ViewQuery {
+    /// Can't be (de)serialized. Use `Raw` for schemas stored as data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Select(Select),
+    Raw(String),
+}
+
+impl ToSql for ViewQuery {
+    fn write_sql(&self, buf: &mut String, dialect: Dialect) {
+        match self {
+            ViewQuery::Select(s) => s.write_sql(buf, dialect),
+            ViewQuery::Raw(s) => buf.push_str(s),
+        }
+    }
+}
+
+impl From<Select> for ViewQuery {
+    fn from(select: Select) -> Self {
+        ViewQuery::Select(select)
+    }
+}
+
+impl From<String> for ViewQuery {
+    fn from(raw: String) -> Self {
+        ViewQuery::Raw(raw)
+    }
+}
+
+impl From<&str> for ViewQuery {
+    fn from(raw: &str) -> Self {
+        ViewQuery::Raw(raw.to_string())
+    }
+}
+
+/// A view, or a materialized view, which stores the result of its query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct View {
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub schema: Option<String>,
+    pub name: String,
+    pub query: ViewQuery,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub materialized: bool,
+}
+
+impl View {
+    pub fn new(name: &str, query: impl Into<ViewQuery>) -> Self {
+        View {
+            schema: None,
+            name: name.to_string(),
+            query: query.into(),
+            materialized: false,
+        }
+    }
+
+    pub fn materialized(mut self) -> Self {
+        self.materialized = true;
+        self
+    }
+
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.schema = Some(schema.to_string());
+        self
+    }
+
+    /// Whether the view's query reads `table` in `schema`. Raw queries are searched for the
+    /// table name. References without a schema, or a `schema` of `None`, match any schema.
+    pub fn reads_table(&self, schema: Option<&str>, table: &str) -> bool {
+        let in_schema = |s: Option<&str>| {
+            s.is_none_or(|s| schema.is_none_or(|schema| s.eq_ignore_ascii_case(schema)))
+        };
+        match &self.query {
+            ViewQuery::Select(select) => {
+                select
+                    .from
+                    .as_ref()
+                    .is_some_and(|f| f.table == table && in_schema(f.schema.as_deref()))
+                    || select.join.iter().any(|j| match &j.table {
+                        JoinTable::Table { schema, table: t } => {
+                            t == table && in_schema(schema.as_deref())
+                        }
+                        JoinTable::Select(_) => false,
+                    })
+            }
+            ViewQuery::Raw(raw) => qualified_words(raw)
+                .into_iter()
+                .any(|(s, word)| word.eq_ignore_ascii_case(table) && in_schema(s)),
+        }
+    }
+
+    /// Whether the view's query may read `column` of `table` in `schema`. Queries are searched
+    /// for the column name, and a `*` is taken to read every column.
+    pub fn reads_column(&self, schema: Option<&str>, table: &str, column: &str) -> bool {
+        let sql = self.query.to_sql(Dialect::Postgres);
+        self.reads_table(schema, table)
+            && (sql.contains('*') || words(&sql).any(|word| word.eq_ignore_ascii_case(column)))
+    }
+
+    /// Compare the queries of two views, allowing for differences in quoting, whitespace and
+    /// casts. Postgres rewrites the definitions of the views it stores, so normalize views
+    /// before comparing them to the database, e.g. with `normalize_views` in sqlmo_sqlx.
+    pub fn lossy_eq(&self, other: &View) -> bool {
+        let normalize = |v: &View| {
+            let sql = v.query.to_sql(Dialect::Postgres);
+            let sql = sql.trim().trim_end_matches(';').replace('"', "");
+            Expr::Raw(sql.split_whitespace().collect::<Vec<_>>().join(" "))
+        };
+        self.materialized == other.materialized && normalize(self).lossy_eq(&normalize(other))
+    }
+}
+
+fn words(sql: &str) -> impl Iterator<Item = &str> {
+    sql.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+}
+
+/// The words of `sql`, each with the word qualifying it, as in `schema.table`.
+fn qualified_words(sql: &str) -> Vec<(Option<&str>, &str)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut spans = vec![];
+    let mut start = None;
+    for (i, c) in sql.char_indices().chain([(sql.len(), ' ')]) {
+        match (start, is_word(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                spans.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    let mut words = vec![];
+    for (i, &(start, end)) in spans.iter().enumerate() {
+        let qualifier = i.checked_sub(1).map(|j| spans[j]).filter(|&(_, previous)| {
+            sql[previous..start].chars().filter(|&c| c != '"').eq(['.'])
+        });
+        words.push((qualifier.map(|(s, e)| &sql[s..e]), &sql[start..end]));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view() {
+        let desired = View::new(
+            "active_user",
+            Select::default().select_raw("id").from("user").where_raw("active"),
+        );
+        let current = View::new(
+            "active_user",
+            " SELECT id\n   FROM \"user\"\n  WHERE active;",
+        );
+        assert!(desired.reads_table(Some("public"), "user"));
+        assert!(current.reads_table(None, "user"));
+        assert!(!current.reads_table(None, "team"));
+        assert!(current.reads_column(None, "user", "active"));
+        assert!(!current.reads_column(None, "user", "email"));
+        assert!(!current.reads_column(None, "team", "active"));
+        let qualified = View::new("admin", r#"SELECT id FROM auth."user" JOIN team ON true"#);
+        assert!(qualified.reads_table(Some("auth"), "user"));
+        assert!(!qualified.reads_table(Some("public"), "user"));
+        assert!(qualified.reads_table(Some("public"), "team"));
+        assert!(desired.lossy_eq(&current));
+        assert!(!desired.lossy_eq(&current.clone().materialized()));
+    }
+}
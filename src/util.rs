@@ -6,6 +6,8 @@ pub(crate) trait SqlExtension {
     fn push_sql<T: ToSql>(&mut self, sql: &T, dialect: Dialect);
     fn push_sql_sequence<T: ToSql>(&mut self, sql: &[T], separator: &str, dialect: Dialect);
     fn push_quoted_sequence(&mut self, sql: &[String], separator: &str);
+    fn push_literal(&mut self, s: &str, dialect: Dialect);
+    fn push_literal_sequence(&mut self, values: &[String], separator: &str, dialect: Dialect);
 }
 
 impl SqlExtension for String {
@@ -32,7 +34,7 @@ impl SqlExtension for String {
 
     fn push_sql_sequence<T: ToSql>(&mut self, sql: &[T], separator: &str, dialect: Dialect) {
         let mut first = true;
-        for s in sql.into_iter() {
+        for s in sql.iter() {
             if !first {
                 self.push_str(separator);
             }
@@ -51,6 +53,28 @@ impl SqlExtension for String {
             first = false;
         }
     }
+
+    /// Push a single-quoted string literal, escaping any single quotes, and backslashes in MySQL.
+    fn push_literal(&mut self, s: &str, dialect: Dialect) {
+        self.push('\'');
+        let s = s.replace('\'', "''");
+        match dialect {
+            Dialect::Mysql => self.push_str(&s.replace('\\', "\\\\")),
+            _ => self.push_str(&s),
+        }
+        self.push('\'');
+    }
+
+    fn push_literal_sequence(&mut self, values: &[String], separator: &str, dialect: Dialect) {
+        let mut first = true;
+        for s in values {
+            if !first {
+                self.push_str(separator);
+            }
+            self.push_literal(s, dialect);
+            first = false;
+        }
+    }
 }
 
 pub fn pkey_column_names(schema: &str) -> Vec<String> {